@@ -1,50 +1,176 @@
 use vector2::Vector2;
 use player::PlayerSide;
-use frisbee::ThrowDirection;
-use game_engine::{ GameEngine, StateOfGame };
+use frisbee::{ ThrowDirection, THROW_DIRECTIONS };
+use game_engine::{ GameEngine, StateOfGame, ExplorationStrategy, PLAYER_DASH_POWER };
 
-use rand::Rng;
-use std::collections::HashMap;
+use rand::{ Rng, SeedableRng };
+use rand::rngs::SmallRng;
+use std::collections::{ HashMap, HashSet, VecDeque };
+use std::fmt;
+use std::sync::{ Arc, RwLock };
+use std::time::{ Duration, Instant };
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum AgentType {
     HumanPlayer = 0,
     Random,
     RandomRollout,
     Dijkstra,
-    TabularQLearning, 
+    TabularQLearning,
+    Minimax,
+    MCTS,
+    Sarsa,
+    AStar,
+    PredictiveCatch,
+    DoubleQLearning,
+    TimedRollout,
+    Seek,
     None
 }
 
-#[derive(Clone, Copy, Debug)]
+// Metadata for the frontends, in one place rather than in each of them
+impl AgentType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AgentType::HumanPlayer => "Human",
+            AgentType::Random => "Random",
+            AgentType::RandomRollout => "Random rollout",
+            AgentType::Dijkstra => "Dijkstra",
+            AgentType::TabularQLearning => "Q-learning",
+            AgentType::Minimax => "Minimax",
+            AgentType::MCTS => "MCTS",
+            AgentType::Sarsa => "SARSA",
+            AgentType::AStar => "A*",
+            AgentType::PredictiveCatch => "Predictive catch",
+            AgentType::DoubleQLearning => "Double Q-learning",
+            AgentType::TimedRollout => "Timed rollout",
+            AgentType::Seek => "Seek",
+            AgentType::None => "None",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AgentType::HumanPlayer => "Plays the inputs of a human player.",
+            AgentType::Random => "Moves, dashes and throws at random.",
+            AgentType::RandomRollout => "Plays out every intent against a random opponent and keeps the best scoring one.",
+            AgentType::Dijkstra => "Searches the cheapest sequence of intents reaching the best score.",
+            AgentType::TabularQLearning => "Learns the value of each action in a table of game states.",
+            AgentType::Minimax => "Searches a few frames ahead assuming the opponent plays its best.",
+            AgentType::MCTS => "Monte Carlo tree search over the intents of both players.",
+            AgentType::Sarsa => "Learns on-policy in a table of game states.",
+            AgentType::AStar => "Searches the first intents grabbing the frisbee or scoring, guided by the distance to the frisbee.",
+            AgentType::PredictiveCatch => "Runs to where the frisbee will be instead of where it is.",
+            AgentType::DoubleQLearning => "Q-learning with two tables to avoid overestimating values.",
+            AgentType::TimedRollout => "Random rollouts for as long as its time budget allows.",
+            AgentType::Seek => "Heads straight for the frisbee and throws it straight at the goal.",
+            AgentType::None => "No agent.",
+        }
+    }
+
+    // Agents learning in the engine's Q-tables, the only ones whose tables are worth saving
+    pub fn is_learning(&self) -> bool {
+        uses_q_values(*self)
+    }
+}
+
+// Vectors compare exactly, intents only match if they were built from the same values
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Intent {
     None,
     Move(Vector2),
     Dash(Vector2),
     Throw(::frisbee::ThrowDirection),
+    Grab, // Catches the frisbee within `GameEngine::catch_radius`, the only way to catch it when `GameEngine::requires_grab`
 }
 
-fn simulation(engine: &mut GameEngine, side: &PlayerSide, intent: Intent, nb_frames : f64) -> (i8, Intent) {
-    let intents = match *side {
-        PlayerSide::Left => (intent, Intent::None),
-        PlayerSide::Right => (Intent::None, intent),
+// Short form for logs, like `Throw(Up)` or `Move(0.71, 0.71)`
+impl fmt::Display for Intent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Intent::None => write!(f, "None"),
+            Intent::Move(dir) => write!(f, "Move({:.2}, {:.2})", dir.x, dir.y),
+            Intent::Dash(dir) => write!(f, "Dash({:.2}, {:.2})", dir.x, dir.y),
+            Intent::Throw(ThrowDirection::Custom(dir)) => write!(f, "Throw(Custom({:.2}, {:.2}))", dir.x, dir.y),
+            Intent::Throw(ThrowDirection::Curved { base, curve }) => {
+                write!(f, "Throw(Curved({:.2}, {:.2}, curve {:.2}))", base.x, base.y, curve)
+            },
+            Intent::Throw(dir) => write!(f, "Throw({:?})", dir),
+            Intent::Grab => write!(f, "Grab"),
+        }
+    }
+}
+
+// Plays `intent` for `side`, then lets its agent play up to `frames` more frames or until the round ends, the opponent plays `opponent` on every frame
+// Returns how much the score of `side` minus its opponent's changed, so conceding a goal counts against the intent
+// and a side that is behind still sees a scoring intent as a gain
+pub fn simulate_intent(engine: &mut GameEngine, side: PlayerSide, intent: Intent, frames: u32, opponent: Intent) -> i8 {
+    simulate_committed(engine, side, intent, 1, frames, opponent)
+}
+
+// Same as `simulate_intent` with `intent` played on the first `commit_frames` frames, or until the round ends
+pub fn simulate_committed(engine: &mut GameEngine, side: PlayerSide, intent: Intent, commit_frames: u32, frames: u32, opponent: Intent) -> i8 {
+    simulate(engine, side, intent, commit_frames, frames, Some(opponent))
+}
+
+// Same as `simulate_committed` with the opponent played by the engine's agent, it stands still on the first frame
+pub fn simulate_against_agent(engine: &mut GameEngine, side: PlayerSide, intent: Intent, commit_frames: u32, frames: u32) -> i8 {
+    simulate(engine, side, intent, commit_frames, frames, None)
+}
+
+fn simulate(engine: &mut GameEngine, side: PlayerSide, intent: Intent, commit_frames: u32, frames: u32, opponent: Option<Intent>) -> i8 {
+    let lead = score_lead(engine, side);
+    let first_opponent = opponent.unwrap_or(Intent::None);
+    let intents = match side {
+        PlayerSide::Left => (intent, first_opponent),
+        PlayerSide::Right => (first_opponent, intent),
     };
 
     engine.step(intents);
 
-    for _i in 0..nb_frames as i16 {
-        engine.epoch(HumanIntent::IDLE, HumanIntent::IDLE);
+    // Only the agents whose intent is played are asked, so none of them learns from or draws for an intent thrown away
+    let mut committed = 1;
+    while committed < commit_frames && engine.state_of_game == StateOfGame::Playing {
+        let other = match opponent {
+            Some(opponent) => opponent,
+            None => engine.select_intent(other_side(side), HumanIntent::IDLE),
+        };
+        let intents = match side {
+            PlayerSide::Left => (intent, other),
+            PlayerSide::Right => (other, intent),
+        };
+        engine.step(intents);
+        committed += 1;
+    }
+
+    for _i in 0..frames {
+        match opponent {
+            Some(opponent) => {
+                let own = engine.select_intent(side, HumanIntent::IDLE);
+                let intents = match side {
+                    PlayerSide::Left => (own, opponent),
+                    PlayerSide::Right => (opponent, own),
+                };
+                engine.step(intents);
+            },
+            None => engine.epoch(HumanIntent::IDLE, HumanIntent::IDLE),
+        };
         if engine.state_of_game != StateOfGame::Playing {
             break;
         }
     }
 
-    let score = match side {
-        PlayerSide::Left => engine.players.0.score,
-        PlayerSide::Right => engine.players.1.score,
-    };
+    score_lead(engine, side).saturating_sub(lead)
+}
 
-    (score, intent)
+// Score of `side` minus its opponent's
+fn score_lead(engine: &GameEngine, side: PlayerSide) -> i8 {
+    let (own, opponent) = match side {
+        PlayerSide::Left => (engine.players.0.score, engine.players.1.score),
+        PlayerSide::Right => (engine.players.1.score, engine.players.0.score),
+    };
+    own.saturating_sub(opponent)
 }
 
 pub fn agent_type_from_i8(side: i8) -> AgentType {
@@ -54,16 +180,139 @@ pub fn agent_type_from_i8(side: i8) -> AgentType {
         2 => AgentType::RandomRollout,
         3 => AgentType::Dijkstra,
         4 => AgentType::TabularQLearning,
+        5 => AgentType::Minimax,
+        6 => AgentType::MCTS,
+        7 => AgentType::Sarsa,
+        8 => AgentType::AStar,
+        9 => AgentType::PredictiveCatch,
+        10 => AgentType::DoubleQLearning,
+        11 => AgentType::TimedRollout,
+        12 => AgentType::Seek,
         _ => AgentType::None
     }
 }
 
-pub trait Agent {
+// Same as `agent_type_from_i8` with an error for the values that aren't an agent
+pub fn try_agent_type_from_i8(agent_type: i8) -> Result<AgentType, String> {
+    match agent_type_from_i8(agent_type) {
+        AgentType::None => Err(format!("Unknown agent type {}, expected 0 to {}.", agent_type, AgentType::None as i8 - 1)),
+        t => Ok(t),
+    }
+}
+
+// Settings of the agents that search ahead, the other agents ignore them
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AgentParams {
+    pub frames: f64, // Frames per rollout, MCTS iterations, A* expansions or predictive catch horizon
+    pub sim:    i8,  // Rollouts per intent, Dijkstra or Minimax depth
+    pub time_budget: Duration, // Thinking time of the anytime agents on each frame
+}
+
+impl Default for AgentParams {
+    // Low enough to keep the search agents fast enough for batches of matches
+    fn default() -> Self {
+        Self {
+            frames: 60.0,
+            sim:    1,
+            time_budget: Duration::from_millis(10),
+        }
+    }
+}
+
+// Short names of the agent types in specs
+pub fn agent_type_from_name(name: &str) -> Option<AgentType> {
+    let agent_type = match name {
+        "human" => AgentType::HumanPlayer,
+        "random" => AgentType::Random,
+        "rollout" => AgentType::RandomRollout,
+        "dijkstra" => AgentType::Dijkstra,
+        "qlearn" => AgentType::TabularQLearning,
+        "minimax" => AgentType::Minimax,
+        "mcts" => AgentType::MCTS,
+        "sarsa" => AgentType::Sarsa,
+        "astar" => AgentType::AStar,
+        "predictive" => AgentType::PredictiveCatch,
+        "doubleq" => AgentType::DoubleQLearning,
+        "timed" => AgentType::TimedRollout,
+        "seek" => AgentType::Seek,
+        _ => return None,
+    };
+    Some(agent_type)
+}
+
+// An agent type and its settings, as read by `parse_agent_spec`
+pub type AgentSpec = (AgentType, AgentParams);
+
+// Parses `type` or `type:key=val,...`, like `dijkstra:depth=5` or `rollout:frames=30,sim=4`
+// Keys: `frames`, `sim` or its alias `depth`, and `time_ms` for the time budget, the others keep their defaults
+pub fn parse_agent_spec(s: &str) -> Result<AgentSpec, String> {
+    let s = s.trim();
+    let (name, settings) = match s.find(':') {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let agent_type = agent_type_from_name(name.trim()).ok_or_else(|| format!("Unknown agent type {}.", name.trim()))?;
+
+    let mut params = AgentParams::default();
+    for setting in settings.iter().flat_map(|settings| settings.split(',')) {
+        let mut parts = setting.splitn(2, '=');
+        let key = parts.next().unwrap().trim();
+        let value = parts.next().ok_or_else(|| format!("Missing value for {}.", key))?.trim();
+        let invalid = format!("Invalid value {} for {}.", value, key);
+        match key {
+            "frames" => params.frames = value.parse().map_err(|_| invalid)?,
+            "sim" | "depth" => params.sim = value.parse().map_err(|_| invalid)?,
+            "time_ms" => params.time_budget = Duration::from_millis(value.parse().map_err(|_| invalid)?),
+            _ => return Err(format!("Unknown key {} for agent {}.", key, name.trim())),
+        };
+    }
+    Ok((agent_type, params))
+}
+
+// Parses a matchup of two specs, like `dijkstra:depth=5 vs qlearn`, left agent first
+pub fn parse_matchup(s: &str) -> Result<(AgentSpec, AgentSpec), String> {
+    let mut specs = s.splitn(2, " vs ");
+    let left = specs.next().unwrap();
+    let right = specs.next().ok_or_else(|| format!("Expected two agents separated by vs in {}.", s))?;
+    Ok((parse_agent_spec(left)?, parse_agent_spec(right)?))
+}
+
+pub fn make_agent(agent_type: AgentType) -> Box<dyn Agent> {
+    make_agent_with(agent_type, AgentParams::default())
+}
+
+pub fn make_agent_with(agent_type: AgentType, params: AgentParams) -> Box<dyn Agent> {
+    let AgentParams { frames, sim, time_budget } = params;
+    match agent_type {
+        AgentType::Random =>           Box::new(RandomAgent::new()),
+        AgentType::HumanPlayer =>      Box::new(HumanPlayerAgent {}),
+        AgentType::RandomRollout =>    Box::new(RandomRolloutAgent {commit_frames: 1, playout_frames: frames, sim, throws: THROW_DIRECTIONS.to_vec(), curved_throws: false, opponent_policy: AgentType::Random}),
+        AgentType::Dijkstra =>         Box::new(DijkstraAgent::new(sim.max(1) as u32)),
+        AgentType::TabularQLearning => Box::new(TabularQLearningAgent::new()),
+        AgentType::Minimax =>          Box::new(MinimaxAgent::new(sim.max(1) as u8)),
+        AgentType::MCTS =>             Box::new(MCTSAgent::new(frames as u32, ::std::f64::consts::SQRT_2, ::rand::thread_rng().gen())),
+        AgentType::Sarsa =>            Box::new(SarsaAgent::new()),
+        AgentType::AStar =>            Box::new(AStarAgent::new(frames.max(1.0) as u32)),
+        AgentType::PredictiveCatch =>  Box::new(PredictiveCatchAgent::new(frames.max(1.0) as u32)),
+        AgentType::DoubleQLearning =>  Box::new(DoubleQLearningAgent {}),
+        AgentType::TimedRollout =>     Box::new(TimedRolloutAgent::new(frames, time_budget)),
+        AgentType::Seek =>             Box::new(SeekAgent {}),
+        AgentType::None =>             panic!("Invalid agent type."),
+    }
+}
+
+// Agents are sent across threads by the parallel searches
+pub trait Agent: Send {
     fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent;
     fn get_type(&self) -> AgentType;
 
-    fn get_random_direction(&self) -> Vector2 {
-        let mut rng = ::rand::thread_rng();
+    // Called by the engine on the last frame of an episode, right before it resets
+    fn end_episode(&mut self, _side: PlayerSide, _engine: &mut GameEngine) {}
+
+    // Restarts the random generator of agents that keep their own instead of drawing from the engine's
+    fn reseed(&mut self, _seed: u64) {}
+
+    fn get_random_direction(&self, rng: &mut SmallRng) -> Vector2 {
         let dir = Vector2::new(
             rng.gen_range(-1.0, 1.0),
             rng.gen_range(-1.0, 1.0)
@@ -72,22 +321,53 @@ pub trait Agent {
     }
 }
 
-pub struct RandomAgent {}
+// Favors the straight throw over the others, closer to what a human player does
+pub const MIDDLE_BIASED_THROW_WEIGHTS: [f32; 5] = [1.0, 1.0, 4.0, 1.0, 1.0];
+
+pub struct RandomAgent {
+    pub throw_weights: Option<[f32; 5]>, // Weights of `THROW_DIRECTIONS`, the throws are uniform when None
+}
+
+impl RandomAgent {
+    pub fn new() -> Self {
+        Self {
+            throw_weights: None,
+        }
+    }
+
+    pub fn middle_biased() -> Self {
+        Self {
+            throw_weights: Some(MIDDLE_BIASED_THROW_WEIGHTS),
+        }
+    }
+}
+
+impl Default for RandomAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Agent for RandomAgent {
     fn get_type(&self) -> AgentType {
         AgentType::Random
     }
     fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
-        let mut rng = ::rand::thread_rng();
+        let can_dash = engine.can_dash(side);
+        let held_by_player = engine.frisbee.held_by_player;
+        let rng = engine.rng_for(side);
 
-        match engine.frisbee.held_by_player {
+        match held_by_player {
             Some(held_side) if held_side == side => {
                 // The agent holds the frisbee
                 let rand = rng.gen_range(0.0, 1.0);
                 if rand < 0.25 {
                     // Throw
-                    return Intent::Throw(::frisbee::random_throw_direction());
+                    let dir = match self.throw_weights {
+                        Some(ref weights) => ::frisbee::random_throw_direction_weighted(weights, rng),
+                        None => ::frisbee::random_throw_direction(rng),
+                    };
+                    return Intent::Throw(dir);
                 } else {
                     // Wait, throw later
                 }
@@ -97,12 +377,12 @@ impl Agent for RandomAgent {
                 let rand = rng.gen_range(0.0, 1.0);
                 if rand < 0.5 {
                     // Move
-                    let dir = self.get_random_direction();
+                    let dir = self.get_random_direction(rng);
                     return Intent::Move(dir);
                 } else if rand < 0.6 {
-                    // Dash
-                    let dir = self.get_random_direction();
-                    return Intent::Dash(dir);
+                    // Dash, or move while the dash is unavailable
+                    let dir = self.get_random_direction(rng);
+                    return if can_dash { Intent::Dash(dir) } else { Intent::Move(dir) };
                 } else {
                     // Wait
                 }
@@ -116,6 +396,8 @@ impl Agent for RandomAgent {
 pub struct HumanPlayerAgent {}
 
 bitflags! {
+    #[repr(transparent)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct HumanIntent: u8 {
         const IDLE  = 0;
         const UP    = 1;
@@ -123,54 +405,125 @@ bitflags! {
         const LEFT  = 4;
         const RIGHT = 8;
         const THROW = 16;
+        const GRAB  = 32;
+    }
+}
+
+// Encoding of the inputs available to the Q-learners, an action index is the position of its input in `INPUTS`
+// The tables hold one value per action, so changing the inputs changes the shape of every table
+pub struct ActionSpace;
+
+impl ActionSpace {
+    pub const INPUTS: [HumanIntent; 18] = [
+        HumanIntent::IDLE,
+        HumanIntent::UP,
+        HumanIntent::DOWN,
+        HumanIntent::LEFT,
+        HumanIntent::RIGHT,
+        HumanIntent::UP.union(HumanIntent::LEFT),
+        HumanIntent::UP.union(HumanIntent::RIGHT),
+        HumanIntent::DOWN.union(HumanIntent::LEFT),
+        HumanIntent::DOWN.union(HumanIntent::RIGHT),
+        HumanIntent::THROW.union(HumanIntent::UP),
+        HumanIntent::THROW.union(HumanIntent::DOWN),
+        HumanIntent::THROW.union(HumanIntent::LEFT),
+        HumanIntent::THROW.union(HumanIntent::RIGHT),
+        HumanIntent::THROW.union(HumanIntent::UP).union(HumanIntent::LEFT),
+        HumanIntent::THROW.union(HumanIntent::UP).union(HumanIntent::RIGHT),
+        HumanIntent::THROW.union(HumanIntent::DOWN).union(HumanIntent::LEFT),
+        HumanIntent::THROW.union(HumanIntent::DOWN).union(HumanIntent::RIGHT),
+        HumanIntent::GRAB,
+    ];
+
+    pub const fn len() -> usize {
+        Self::INPUTS.len()
+    }
+
+    // Inputs outside of the action space, like a lone throw, map to idle
+    pub fn from_human_intent(input: HumanIntent) -> usize {
+        Self::INPUTS.iter().position(|&i| i == input).unwrap_or(0)
+    }
+
+    pub fn to_human_intent(index: usize) -> HumanIntent {
+        Self::INPUTS.get(index).cloned().unwrap_or(HumanIntent::IDLE)
+    }
+
+    pub fn to_intent(index: usize, engine: &GameEngine, side: PlayerSide) -> Intent {
+        human_intent_to_intent(engine, Self::to_human_intent(index), side)
     }
 }
 
 pub fn human_intent_to_index(val: HumanIntent) -> u8 {
-    if val == HumanIntent::UP { return 1; }
-    if val == HumanIntent::DOWN { return 2; }
-    if val == HumanIntent::LEFT { return 3; }
-    if val == HumanIntent::RIGHT { return 4; }
-    if val == HumanIntent::UP | HumanIntent::LEFT { return 5; }
-    if val == HumanIntent::UP | HumanIntent::RIGHT { return 6; }
-    if val == HumanIntent::DOWN | HumanIntent::LEFT { return 7; }
-    if val == HumanIntent::DOWN | HumanIntent::RIGHT { return 8; }
-    if val == HumanIntent::THROW | HumanIntent::UP { return 9; }
-    if val == HumanIntent::THROW | HumanIntent::DOWN { return 10; }
-    if val == HumanIntent::THROW | HumanIntent::LEFT { return 11; }
-    if val == HumanIntent::THROW | HumanIntent::RIGHT { return 12; }
-    if val == HumanIntent::THROW | HumanIntent::UP | HumanIntent::LEFT { return 13; }
-    if val == HumanIntent::THROW | HumanIntent::UP | HumanIntent::RIGHT { return 14; }
-    if val == HumanIntent::THROW | HumanIntent::DOWN | HumanIntent::LEFT { return 15; }
-    if val == HumanIntent::THROW | HumanIntent::DOWN | HumanIntent::RIGHT { return 16; }
-    0
+    ActionSpace::from_human_intent(val) as u8
 }
 
 pub fn human_intent_from_index(idx: u8) -> HumanIntent {
-    if idx == 1 { return HumanIntent::UP; }
-    if idx == 2 { return HumanIntent::DOWN; }
-    if idx == 3 { return HumanIntent::LEFT; }
-    if idx == 4 { return HumanIntent::RIGHT; }
-    if idx == 5 { return HumanIntent::UP | HumanIntent::LEFT; }
-    if idx == 6 { return HumanIntent::UP | HumanIntent::RIGHT; }
-    if idx == 7 { return HumanIntent::DOWN | HumanIntent::LEFT; }
-    if idx == 8 { return HumanIntent::DOWN | HumanIntent::RIGHT; }
-    if idx == 9 { return HumanIntent::THROW | HumanIntent::UP; }
-    if idx == 10 { return HumanIntent::THROW | HumanIntent::DOWN; }
-    if idx == 11 { return HumanIntent::THROW | HumanIntent::LEFT; }
-    if idx == 12 { return HumanIntent::THROW | HumanIntent::RIGHT; }
-    if idx == 13 { return HumanIntent::THROW | HumanIntent::UP | HumanIntent::LEFT; }
-    if idx == 14 { return HumanIntent::THROW | HumanIntent::UP | HumanIntent::RIGHT; }
-    if idx == 15 { return HumanIntent::THROW | HumanIntent::DOWN | HumanIntent::LEFT; }
-    if idx == 16 { return HumanIntent::THROW | HumanIntent::DOWN | HumanIntent::RIGHT; }
-    HumanIntent::IDLE
+    ActionSpace::to_human_intent(idx as usize)
 }
 
-pub fn human_intent_to_intent(engine: &GameEngine, input: HumanIntent, side: PlayerSide) -> Intent {
-    let has_frisbee = match engine.frisbee.held_by_player {
-        Some(held_by) if held_by == side => true,
-        _ => false,
+// Sector boundaries of an analog stick throw, in radians away from the opponent's goal on either side:
+// straight up to `middle`, light up to `light`, steep up to `backward` and flat toward the player's own goal past it
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnalogThrowConfig {
+    pub middle:   f64,
+    pub light:    f64,
+    pub backward: f64,
+}
+
+impl Default for AnalogThrowConfig {
+    // Centered on the eight directions of the digital input
+    fn default() -> Self {
+        use std::f64::consts::PI;
+        Self {
+            middle:   PI / 8.0,
+            light:    3.0 * PI / 8.0,
+            backward: 7.0 * PI / 8.0,
+        }
+    }
+}
+
+// Same as `human_intent_to_intent` for an analog stick, the throw direction is picked by the angle of `dir`
+// against `engine.analog_throws`, a zero `dir` stands still or throws straight
+// Moves keep the length of `dir` up to 1 so a half tilted stick walks at half speed
+pub fn human_intent_to_intent_analog(engine: &GameEngine, dir: Vector2, throw: bool, side: PlayerSide) -> Intent {
+    let has_frisbee = engine.frisbee.held_by_player == Some(side);
+
+    if !throw {
+        if dir.x == 0.0 && dir.y == 0.0 {
+            return Intent::None;
+        }
+        return Intent::Move(if dir.length() > 1.0 { dir.normalized() } else { dir });
+    }
+    let dir = dir.normalized();
+    if !has_frisbee {
+        return Intent::Dash(dir);
+    }
+    if dir.x == 0.0 && dir.y == 0.0 {
+        return Intent::Throw(ThrowDirection::Middle);
+    }
+
+    // Angle from the direction of the opponent's goal, positive upward
+    let forward = match side {
+        PlayerSide::Left => 1.0,
+        PlayerSide::Right => -1.0,
+    };
+    let angle = dir.y.atan2(dir.x * forward);
+    let sectors = engine.analog_throws;
+    let throw_dir = if angle.abs() <= sectors.middle {
+        ThrowDirection::Middle
+    } else if angle.abs() <= sectors.light {
+        if angle > 0.0 { ThrowDirection::LightUp } else { ThrowDirection::LightDown }
+    } else if angle.abs() <= sectors.backward {
+        if angle > 0.0 { ThrowDirection::Up } else { ThrowDirection::Down }
+    } else {
+        ThrowDirection::Custom(Vector2::new(-forward, 0.0))
     };
+    Intent::Throw(throw_dir)
+}
+
+pub fn human_intent_to_intent(engine: &GameEngine, input: HumanIntent, side: PlayerSide) -> Intent {
+    let has_frisbee = engine.frisbee.held_by_player == Some(side);
 
     let mut dir = Vector2::zero();
     if input.contains(HumanIntent::UP) {
@@ -204,11 +557,16 @@ pub fn human_intent_to_intent(engine: &GameEngine, input: HumanIntent, side: Pla
                 } else {
                     throw_dir = ThrowDirection::Down;
                 }
+            } else if input.intersects(HumanIntent::LEFT | HumanIntent::RIGHT) {
+                // Flat throw in the pressed direction, even towards the player's own goal
+                throw_dir = ThrowDirection::Custom(Vector2::new(dir.x, 0.0));
             }
             Intent::Throw(throw_dir)
         } else {
             Intent::Dash(dir)
         }
+    } else if input.contains(HumanIntent::GRAB) {
+        Intent::Grab
     } else {
         if dir.x == 0.0 && dir.y == 0.0 {
             Intent::None
@@ -223,82 +581,311 @@ impl Agent for HumanPlayerAgent {
         AgentType::HumanPlayer
     }
     fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
-        let input = match side {
-            PlayerSide::Left => engine.inputs.0,
-            PlayerSide::Right => engine.inputs.1,
+        let (input, analog) = match side {
+            PlayerSide::Left => (engine.inputs.0, engine.inputs_analog.0),
+            PlayerSide::Right => (engine.inputs.1, engine.inputs_analog.1),
         };
+        // A grab has no direction, the stick doesn't matter
+        if analog != Vector2::zero() && !input.contains(HumanIntent::GRAB) {
+            return human_intent_to_intent_analog(engine, analog, input.contains(HumanIntent::THROW), side);
+        }
         human_intent_to_intent(engine, input, side)
     }
 }
 
-pub struct RandomRolloutAgent {pub frames : f64,pub sim: i8}
+// `opponent_policy` drives the other player during the rollouts, `HumanPlayer` stands still
+// Set it with `set_opponent_policy`, an `AgentType::None` set directly stands still too
+#[derive(Clone)]
+pub struct RandomRolloutAgent {
+    pub commit_frames:   u32, // Frames the intent evaluated is played for, the opponent acts during them too
+    pub playout_frames:  f64, // Frames the agents play afterwards
+    pub sim:             i8,
+    pub throws:          Vec<ThrowDirection>,
+    pub curved_throws:   bool,
+    pub opponent_policy: AgentType,
+}
+
+// Rollout `index` plays intent `index % intents.len()` on `scratch`, seeded with `base_seed + index`
+// so its result doesn't depend on which thread runs it or in what order
+// `opponent` plays the other side when set, reseeded the same way and ending its episode after the rollout,
+// otherwise the random agent left by `copy_in` does
+#[allow(clippy::too_many_arguments)]
+fn rollout(start: &GameEngine, scratch: &mut GameEngine, side: PlayerSide, intents: &[Intent], commit_frames: u32, playout_frames: f64, base_seed: u64, index: usize, opponent: &mut Option<Box<dyn Agent>>) -> i8 {
+    let seed = base_seed.wrapping_add(index as u64);
+    start.copy_in(scratch);
+    let opponent_side = other_side(side);
+    if let Some(mut agent) = opponent.take() {
+        agent.reseed(seed);
+        match opponent_side {
+            PlayerSide::Left => scratch.agents.0 = Some(agent),
+            PlayerSide::Right => scratch.agents.1 = Some(agent),
+        };
+        scratch.seed_rng(seed);
+        let score = simulate_against_agent(scratch, side, intents[index % intents.len()], commit_frames, playout_frames as u32);
+        let mut agent = match opponent_side {
+            PlayerSide::Left => scratch.agents.0.take().unwrap(),
+            PlayerSide::Right => scratch.agents.1.take().unwrap(),
+        };
+        // Nothing it kept from this rollout carries over to the next one
+        agent.end_episode(opponent_side, scratch);
+        *opponent = Some(agent);
+        return score;
+    }
+    scratch.seed_rng(seed);
+    simulate_against_agent(scratch, side, intents[index % intents.len()], commit_frames, playout_frames as u32)
+}
+
+// Highest scoring rollout as (score, index), the first one wins ties and nothing scoring 0 is kept
+// The rollouts after one reaching `winning_score` are never run, none of them could do better
+#[cfg(feature = "parallel")]
+fn best_rollout<I: Iterator<Item = (i8, usize)>>(rollouts: I, winning_score: i8) -> Option<(i8, usize)> {
+    let mut best: Option<(i8, usize)> = None;
+    for (score, index) in rollouts {
+        if score > best.map_or(0, |b| b.0) {
+            best = Some((score, index));
+        }
+        if score >= winning_score {
+            break;
+        }
+    }
+    best
+}
+
+impl RandomRolloutAgent {
+    pub fn set_opponent_policy(&mut self, policy: AgentType) -> Result<(), String> {
+        if policy == AgentType::None {
+            return Err("Invalid opponent policy.".to_string());
+        }
+        self.opponent_policy = policy;
+        Ok(())
+    }
+
+    // Built once per search and reused by its rollouts, `copy_in` already leaves a random agent
+    fn rollout_opponent(&self) -> Option<Box<dyn Agent>> {
+        match self.opponent_policy {
+            AgentType::Random => None,
+            AgentType::None => Some(make_agent(AgentType::HumanPlayer)),
+            policy => Some(make_agent(policy)),
+        }
+    }
+
+    fn rollout_count(&self, intents: &[Intent]) -> usize {
+        self.sim.max(0) as usize * intents.len()
+    }
+
+    // A goal in the best zone, rollouts end with the round so none scores more
+    fn winning_score(engine: &GameEngine) -> i8 {
+        engine.max_goal_points()
+    }
+
+    // Score of every rollout in order, rollout `i` plays the intent `i % intents.len()` of `legal_intents_with_throws`
+    pub fn rollout_scores(&self, side: PlayerSide, engine: &GameEngine, base_seed: u64) -> Vec<i8> {
+        let intents = engine.legal_intents_with_throws(side, &self.throws, self.curved_throws);
+        let mut scratch = GameEngine::new();
+        let mut opponent = self.rollout_opponent();
+        (0..self.rollout_count(&intents))
+            .map(|i| rollout(engine, &mut scratch, side, &intents, self.commit_frames, self.playout_frames, base_seed, i, &mut opponent))
+            .collect()
+    }
+
+    // Every intent with the best score of its rollouts, highest first, ties ordered by the first rollout reaching the score
+    // When no rollout scores, standing still comes first with a score of 0 as that is what the agent plays then
+    pub fn ranked(&self, side: PlayerSide, engine: &GameEngine, base_seed: u64) -> Vec<(Intent, f64)> {
+        let intents = engine.legal_intents_with_throws(side, &self.throws, self.curved_throws);
+        let scores = self.rollout_scores(side, engine, base_seed);
+
+        let mut best: Vec<Option<(i8, usize)>> = vec![None; intents.len()];
+        for (i, score) in scores.into_iter().enumerate() {
+            let candidate = &mut best[i % intents.len()];
+            if candidate.is_none_or(|(best_score, _)| score > best_score) {
+                *candidate = Some((score, i));
+            }
+        }
+        let mut candidates: Vec<(i8, usize)> = best.into_iter().flatten().collect();
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        let mut ranked: Vec<(Intent, f64)> = candidates.into_iter()
+            .map(|(score, i)| (intents[i % intents.len()], score as f64))
+            .collect();
+        if ranked.first().is_none_or(|&(_, score)| score <= 0.0) {
+            ranked.insert(0, (Intent::None, 0.0));
+        }
+        ranked
+    }
+
+    pub fn act_ranked(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Vec<(Intent, f64)> {
+        // Drawn from the engine like `act` does
+        let base_seed = engine.rng_for(side).gen();
+        self.ranked(side, engine, base_seed)
+    }
+
+    // Same intent as the first of `ranked`, stopping at the first rollout that reaches the winning score
+    pub fn search_serial(&self, side: PlayerSide, engine: &GameEngine, base_seed: u64) -> Intent {
+        self.search_serial_counted(side, engine, base_seed).0
+    }
+
+    // Same as `search_serial`, with the number of rollouts played
+    pub fn search_serial_counted(&self, side: PlayerSide, engine: &GameEngine, base_seed: u64) -> (Intent, usize) {
+        let intents = engine.legal_intents_with_throws(side, &self.throws, self.curved_throws);
+        let winning_score = Self::winning_score(engine);
+        let mut scratch = GameEngine::new();
+        let mut opponent = self.rollout_opponent();
+        let mut best: Option<(i8, usize)> = None;
+        let mut count = 0;
+        for i in 0..self.rollout_count(&intents) {
+            let score = rollout(engine, &mut scratch, side, &intents, self.commit_frames, self.playout_frames, base_seed, i, &mut opponent);
+            count += 1;
+            if score > best.map_or(0, |b| b.0) {
+                best = Some((score, i));
+            }
+            if score >= winning_score {
+                break;
+            }
+        }
+        let intent = best.map_or(Intent::None, |(_, i)| intents[i % intents.len()]);
+        (intent, count)
+    }
+
+    // Same result as `search_serial`, with the rollouts split in contiguous chunks over the rayon thread pool
+    #[cfg(feature = "parallel")]
+    pub fn search_parallel(&self, side: PlayerSide, engine: &GameEngine, base_seed: u64) -> Intent {
+        use rayon::prelude::*;
+
+        let intents = engine.legal_intents_with_throws(side, &self.throws, self.curved_throws);
+        let count = self.rollout_count(&intents);
+        if count == 0 {
+            return Intent::None;
+        }
+        let chunks = ::rayon::current_num_threads().min(count);
+        let chunk = count.div_ceil(chunks);
+
+        // Engines hold boxed agents so they can't be shared, each chunk gets its own copy of the start state and opponent
+        let engines: Vec<_> = (0..chunks)
+            .map(|_| {
+                let mut start = GameEngine::new();
+                engine.copy_in(&mut start);
+                (start, GameEngine::new(), self.rollout_opponent())
+            })
+            .collect();
+
+        let intents_ref = &intents;
+        let (commit_frames, playout_frames) = (self.commit_frames, self.playout_frames);
+        let winning_score = Self::winning_score(engine);
+        let chunk_bests: Vec<Option<(i8, usize)>> = engines.into_par_iter().enumerate()
+            .map(|(t, (start, mut scratch, mut opponent))| {
+                let rollouts = (t * chunk..((t + 1) * chunk).min(count))
+                    .map(|i| (rollout(&start, &mut scratch, side, intents_ref, commit_frames, playout_frames, base_seed, i, &mut opponent), i));
+                best_rollout(rollouts, winning_score)
+            })
+            .collect();
+        // Chunks are merged in order, so ties still go to the first rollout
+        let best = best_rollout(chunk_bests.into_iter().flatten(), winning_score);
+        best.map_or(Intent::None, |(_, i)| intents[i % intents.len()])
+    }
+}
 
 impl Agent for RandomRolloutAgent {
     fn get_type(&self) -> AgentType {
         AgentType::RandomRollout
     }
     fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
-        let mut prev = (0, Intent::None);
-        let mut new_engine = GameEngine::new();
-        let player = match side {
-            PlayerSide::Left => &engine.players.0,
-            PlayerSide::Right => &engine.players.1,
-        };
+        // Drawn from the engine so rollouts are reproducible when it is seeded
+        let base_seed = engine.rng_for(side).gen();
 
-        fn run_simulation(prev: &mut (i8, Intent), engine: &GameEngine, new_game_engine: &mut GameEngine, side: &PlayerSide, intent: Intent,frames: f64) {
-            engine.copy_in(new_game_engine);
-            let test = simulation(new_game_engine, side, intent,frames);
-            if prev.0 < test.0 {
-                prev.0 = test.0;
-                prev.1 = test.1;
-            }
+        #[cfg(feature = "parallel")]
+        let intent = self.search_parallel(side, engine, base_seed);
+        #[cfg(not(feature = "parallel"))]
+        let intent = self.search_serial(side, engine, base_seed);
+        intent
+    }
+}
+
+// Anytime version of the random rollouts: plays rounds of one rollout per intent until its time budget is spent,
+// so the time taken on each frame doesn't depend on the situation
+pub struct TimedRolloutAgent {
+    pub frames:        f64,
+    pub time_budget:   Duration,
+    pub throws:        Vec<ThrowDirection>,
+    pub curved_throws: bool,
+}
+
+impl TimedRolloutAgent {
+    pub fn new(frames: f64, time_budget: Duration) -> Self {
+        Self {
+            frames,
+            time_budget,
+            throws: THROW_DIRECTIONS.to_vec(),
+            curved_throws: false,
         }
+    }
 
+    // Best intent found before the budget ran out, with the number of rollouts played
+    // Rollouts are seeded like `RandomRolloutAgent::search_serial`, so `n` full rounds give the same result as `n` simulations
+    pub fn search(&self, side: PlayerSide, engine: &GameEngine, base_seed: u64) -> (Intent, usize) {
+        let start = Instant::now();
+        let intents = engine.legal_intents_with_throws(side, &self.throws, self.curved_throws);
+        if intents.is_empty() {
+            return (Intent::None, 0);
+        }
 
-        for _ in 0..self.sim {
-            match engine.frisbee.held_by_player {
-                Some(held_by) if held_by == side => {
-                    // If the agent holds the frisbee
-                    run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Up),self.frames);
-                    run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::LightUp),self.frames);
-                    run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Middle),self.frames);
-                    run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::LightDown),self.frames);
-                    run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Down),self.frames);
-                },
-                _ => {
-                    // If the agent doesn't hold the frisbee
-                    if player.slide.is_none() {
-                        // Movements are allowed only if the player is not dashing,
-                        // so we're saving computing time if they are dashing
-
-                        // TODO: use `human_intent_to_intent()` to replace the `Vector2::new`s with combined UP / DOWN / LEFT / RIGHT.
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(0.0, 1.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(0.0, -1.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(-1.0, 0.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(1.0, 0.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(-1.0, -1.0).normalized()),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(-1.0, 1.0).normalized()),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(1.0, -1.0).normalized()),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Move(Vector2::new(1.0, 1.0).normalized()),self.frames);
-
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(0.0, 1.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(0.0, -1.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, 0.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(1.0, 0.0)),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, -1.0).normalized()),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, 1.0).normalized()),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(1.0, -1.0).normalized()),self.frames);
-                        run_simulation(&mut prev, &engine, &mut new_engine, &side, Intent::Dash(Vector2::new(1.0, 1.0).normalized()),self.frames);
-                    }
-                }
-            };
+        let mut scratch = GameEngine::new();
+        let mut best: Option<(i8, usize)> = None;
+        let mut count = 0;
+        // Checked after every rollout, the budget is overrun by one rollout at most
+        while start.elapsed() < self.time_budget {
+            let score = rollout(engine, &mut scratch, side, &intents, 1, self.frames, base_seed, count, &mut None);
+            if score > best.map_or(0, |b| b.0) {
+                best = Some((score, count));
+            }
+            count += 1;
         }
+        let intent = best.map_or(Intent::None, |(_, i)| intents[i % intents.len()]);
+        (intent, count)
+    }
+}
 
-        prev.1
+impl Agent for TimedRolloutAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::TimedRollout
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let base_seed = engine.rng_for(side).gen();
+        self.search(side, engine, base_seed).0
     }
 }
 
-pub struct DijkstraAgent {}
+pub struct DijkstraAgent {
+    pub max_depth:     u32,
+    pub throws:        Vec<ThrowDirection>, // Throws tried while holding the frisbee
+    pub curved_throws: bool, // Also search the curved throws
+    pub verbose:       bool, // Keeps the best candidates of every decision in `diagnostics_log`
+    pub weights:       DijkstraWeights,
+    pub diagnostics_log: Option<String>, // Best candidates of the last decision when `verbose` is set, for the caller to log
+}
+
+impl DijkstraAgent {
+    pub fn new(max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            throws: THROW_DIRECTIONS.to_vec(),
+            curved_throws: false,
+            verbose: false,
+            weights: DijkstraWeights::default(),
+            diagnostics_log: None,
+        }
+    }
+
+    // What `act` keeps about the best candidates, nothing unless `verbose` is set
+    pub fn diagnostics(&self, best: &[Node]) -> Option<String> {
+        if !self.verbose {
+            return None;
+        }
+        let lines: Vec<String> = best.iter()
+            .map(|node| format!("candidate {:?}: score {}, cost {}", node.first_intent, node.score, node.cost))
+            .collect();
+        Some(lines.join("\n"))
+    }
+}
 
 pub struct Node {
     pub engine: GameEngine,
@@ -308,7 +895,7 @@ pub struct Node {
 }
 
 
-pub fn get_best(nodes: &Vec<Node>) -> Vec<Node> {
+pub fn get_best(nodes: &[Node]) -> Vec<Node> {
         let mut max_score = 0;
         let mut max_nodes: Vec<Node> = Vec::new();
 
@@ -329,95 +916,140 @@ pub fn get_best(nodes: &Vec<Node>) -> Vec<Node> {
         max_nodes
     }
 
-fn simulation_dij(engine: &mut GameEngine, side: &PlayerSide, intent: Intent, nodes: &mut Vec<Node>, score:  i64, cost: i64) {
-    
+// A pending expansion of the Dijkstra search: `intent` hasn't been applied to `engine` yet
+struct DijkstraWork {
+    engine: GameEngine,
+    intent: Intent,
+    score:  i64,
+    cost:   i64,
+    depth:  u32,
+}
 
-    if cost >= 1000000000000 || engine.state_of_game != StateOfGame::Playing {return;}
-    let intents = match *side {
-        PlayerSide::Left => (intent, Intent::None),
-        PlayerSide::Right => (Intent::None, intent),
-    };
-    let mut add_score = 0;
-    let distance_before = match *side {
-        PlayerSide::Left => (engine.frisbee.pos - engine.players.0.pos).length(),
-        PlayerSide::Right => (engine.frisbee.pos - engine.players.1.pos).length(),
-    };
-    engine.step(intents);
-    let distance_after = match *side {
-        PlayerSide::Left => (engine.frisbee.pos - engine.players.0.pos).length(),
-        PlayerSide::Right => (engine.frisbee.pos - engine.players.1.pos).length(),
-    };
+// Expands the search from `engine` with an explicit work stack, so a deep search can't overflow the native stack
+// `depth` is the number of steps left to simulate, nothing is expanded past it
+#[allow(clippy::too_many_arguments)]
+fn simulation_dij(engine: &GameEngine, side: &PlayerSide, intent: Intent, nodes: &mut Vec<Node>, score: i64, cost: i64, depth: u32, agent: &DijkstraAgent) {
+    let weights = &agent.weights;
+    let mut start = GameEngine::new();
+    engine.copy_in(&mut start);
+    let mut stack = vec![DijkstraWork { engine: start, intent, score, cost, depth }];
+
+    while let Some(work) = stack.pop() {
+        let DijkstraWork { mut engine, intent, score, cost, depth } = work;
+        if depth == 0 || cost >= 1000000000000 || engine.state_of_game != StateOfGame::Playing {
+            continue;
+        }
 
-    if distance_after < distance_before {
-        add_score += 1000;
-    }
-    if distance_after > distance_before {
-        add_score -= 100;
-    }
-    if distance_after == distance_before {
-        add_score -= 50;
-    }
+        let intents = match *side {
+            PlayerSide::Left => (intent, Intent::None),
+            PlayerSide::Right => (Intent::None, intent),
+        };
+        let before = frisbee_distance(&engine, *side);
+        engine.step(intents);
+        let after = frisbee_distance(&engine, *side);
+        let add_score = weights.step_score(before, after, possession(&engine, *side) > 0.0);
+
+        if depth == 1 {
+            nodes.push(Node { engine, first_intent: intent, cost, score: add_score + score });
+            continue;
+        }
 
-    let player = match side {
+        let player = match side {
             PlayerSide::Left => &engine.players.0,
             PlayerSide::Right => &engine.players.1,
-    };
+        };
+        let score = add_score + score;
+        let mut children: Vec<(Intent, i64, i64)> = Vec::new();
+        for child in engine.legal_intents_with_throws(*side, &agent.throws, agent.curved_throws) {
+            let child = match child {
+                Intent::Throw(dir) => (child, score + weights.throw_bonus(dir) + player.score as i64, cost + 1),
+                Intent::Dash(_) => (child, score + player.score as i64 + weights.move_bonus, cost + weights.dash_cost),
+                _ => (child, score + player.score as i64 + weights.move_bonus, cost + 1),
+            };
+            children.push(child);
+        }
 
-    match engine.frisbee.held_by_player {
-        Some(held_by) if held_by == *side =>  add_score = 100000,
-        _ =>{}
-    }; 
-    let mut new_engine = GameEngine::new();
+        // Pushed in reverse so they are expanded in the same order as the original recursion
+        for (child_intent, child_score, child_cost) in children.into_iter().rev() {
+            let mut child = GameEngine::new();
+            engine.copy_in(&mut child);
+            stack.push(DijkstraWork { engine: child, intent: child_intent, score: child_score, cost: child_cost, depth: depth - 1 });
+        }
+        nodes.push(Node { engine, first_intent: intent, cost, score });
+    }
+}
 
-    let mut node_engine = GameEngine::new();
-    engine.copy_in(&mut node_engine);
-    let node = Node { engine: node_engine, first_intent: intent, cost: cost, score: add_score + score as i64 };
-    nodes.push(node);
- 
+// A step changing the distance to the frisbee by less than this makes no progress
+pub const DIJKSTRA_NO_PROGRESS_EPS: f64 = 0.01;
 
-    match engine.frisbee.held_by_player {
-        Some(held_by) if held_by == *side => {
-            // If the agent holds the frisbee
-            simulation_dij(&mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Up), nodes, add_score + score+ 3000 +(player.score) as i64, cost+1);
-            simulation_dij(&mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::LightUp), nodes, add_score + score+ 4000 +(player.score) as i64, cost+1);
-            simulation_dij(&mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Middle), nodes, add_score + score+ 2000 +(player.score) as i64, cost+1);
-            simulation_dij(&mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::LightDown), nodes, add_score + score+ 4000 +(player.score) as i64, cost+1);
-            simulation_dij(&mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Down), nodes, add_score + score+ 3000+(player.score) as i64, cost+1);
-        },
-        _ => {
-            // If the agent doesn't hold the frisbee
-            if player.slide.is_none() {
-                // Movements are allowed only if the player is not dashing,
-                // so we're saving computing time if they are dashing
-
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(0.0, 1.0)), nodes,add_score + score +(player.score + 1) as i64, cost+1);
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(0.0, -1.0)), nodes,add_score + score +(player.score + 1) as i64, cost+1);
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(-1.0, 0.0)), nodes,add_score + score +(player.score + 1) as i64, cost+1);
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(1.0, 0.0)),  nodes,add_score + score +(player.score + 1) as i64, cost+1);
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(-1.0, -1.0).normalized()), nodes,add_score + score +(player.score + 1) as i64, cost+1);
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(-1.0, 1.0).normalized()), nodes,add_score + score +(player.score + 1) as i64, cost+1);
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(1.0, -1.0).normalized()), nodes,add_score + score +(player.score + 1) as i64, cost+1);
-                simulation_dij(&mut new_engine, &side, Intent::Move(Vector2::new(1.0, 1.0).normalized()), nodes,add_score + score +(player.score + 1) as i64, cost+1);
-
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(0.0, 1.0)), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(0.0, -1.0)), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, 0.0)), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(1.0, 0.0)), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, -1.0).normalized()), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, 1.0).normalized()), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(1.0, -1.0).normalized()), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-                simulation_dij(&mut new_engine, &side, Intent::Dash(Vector2::new(1.0, 1.0).normalized()), nodes, add_score + score +(player.score + 1) as i64, cost+4);
-            }
+fn frisbee_distance(engine: &GameEngine, side: PlayerSide) -> f64 {
+    match side {
+        PlayerSide::Left => engine.frisbee.pos.distance(&engine.players.0.pos),
+        PlayerSide::Right => engine.frisbee.pos.distance(&engine.players.1.pos),
+    }
+}
+
+// Everything the Dijkstra search scores its nodes with
+// Each step scores whether it got closer to the frisbee, or holding it after the step, a throw expanded
+// inside the search adds its bonus and a throw at the root only a hundredth of it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DijkstraWeights {
+    pub possession_bonus:    i64, // Replaces the step score when the frisbee is held after the step
+    pub progress_bonus:      i64, // Getting closer to the frisbee
+    pub regress_penalty:     i64, // Getting farther from it
+    pub throw_steep:         i64, // Up and Down
+    pub throw_light:         i64, // LightUp and LightDown
+    pub throw_middle:        i64, // Middle and Custom
+    pub throw_curved:        i64,
+    pub move_bonus:          i64, // Added for every move or dash expanded
+    pub dash_cost:           i64, // Moves and throws cost 1
+    pub no_progress_penalty: i64, // See `DIJKSTRA_NO_PROGRESS_EPS`
+}
+
+impl Default for DijkstraWeights {
+    fn default() -> Self {
+        Self {
+            possession_bonus:    100000,
+            progress_bonus:      1000,
+            regress_penalty:     100,
+            throw_steep:         3000,
+            throw_light:         4000,
+            throw_middle:        2000,
+            throw_curved:        3000,
+            move_bonus:          1,
+            dash_cost:           4,
+            no_progress_penalty: 50,
         }
-    };
+    }
 }
 
-impl Agent for DijkstraAgent {
-    fn get_type(&self) -> AgentType {
-        AgentType::Dijkstra
+impl DijkstraWeights {
+    // Score of a step from the distance to the frisbee before and after it
+    pub fn step_score(&self, before: f64, after: f64, holds: bool) -> i64 {
+        if holds {
+            self.possession_bonus
+        } else if (after - before).abs() < DIJKSTRA_NO_PROGRESS_EPS {
+            -self.no_progress_penalty
+        } else if after < before {
+            self.progress_bonus
+        } else {
+            -self.regress_penalty
+        }
     }
-    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
-        let mut new_engine = GameEngine::new();
+
+    pub fn throw_bonus(&self, dir: ThrowDirection) -> i64 {
+        match dir {
+            ThrowDirection::Up | ThrowDirection::Down => self.throw_steep,
+            ThrowDirection::LightUp | ThrowDirection::LightDown => self.throw_light,
+            ThrowDirection::Middle | ThrowDirection::Custom(_) => self.throw_middle,
+            ThrowDirection::Curved { .. } => self.throw_curved,
+        }
+    }
+}
+
+impl DijkstraAgent {
+    // Every node generated by the search from the current state, up to `max_depth` steps ahead
+    pub fn search(&self, side: PlayerSide, engine: &GameEngine) -> Vec<Node> {
         let player = match side {
             PlayerSide::Left => &engine.players.0,
             PlayerSide::Right => &engine.players.1,
@@ -430,151 +1062,1129 @@ impl Agent for DijkstraAgent {
         let node = Node { engine: node_engine, first_intent: Intent::None, cost: -1, score: player.score as i64 };
         nodes.push(node);
 
-        fn run_simulation(engine: &GameEngine, new_game_engine: &mut GameEngine, side: &PlayerSide, intent: Intent, nodes: &mut Vec<Node>, score: i64) {
-            engine.copy_in(new_game_engine);
+        fn run_simulation(engine: &GameEngine, side: &PlayerSide, intent: Intent, nodes: &mut Vec<Node>, score: i64, agent: &DijkstraAgent) {
             let mut node_engine = GameEngine::new();
             engine.copy_in(&mut node_engine);
-            let node = Node { engine: node_engine, first_intent: intent, cost: -1, score: score as i64 };
+            let node = Node { engine: node_engine, first_intent: intent, cost: -1, score };
             nodes.push(node);
-            simulation_dij(new_game_engine, side, intent, nodes, score, 0);
+            simulation_dij(engine, side, intent, nodes, score, 0, agent.max_depth, agent);
         }
 
 
-        match engine.frisbee.held_by_player {
-            Some(held_by) if held_by == side => {
-                // If the agent holds the frisbee
-                run_simulation(&engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Up), &mut nodes, (player.score + 30) as i64);
-                run_simulation(&engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::LightUp), &mut nodes, (player.score + 40) as i64);
-                run_simulation(&engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Middle), &mut nodes, (player.score + 20) as i64);
-                run_simulation(&engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::LightDown), &mut nodes, (player.score + 40) as i64);
-                run_simulation(&engine, &mut new_engine, &side, Intent::Throw(::frisbee::ThrowDirection::Down), &mut nodes, (player.score + 30) as i64);
-            },
-            _ => {
-                // If the agent doesn't hold the frisbee
-                if player.slide.is_none() {
-                    // Movements are allowed only if the player is not dashing,
-                    // so we're saving computing time if they are dashing
-
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(0.0, 1.0)), &mut nodes,(player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(0.0, -1.0)), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(-1.0, 0.0)), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(1.0, 0.0)), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(-1.0, -1.0).normalized()), &mut nodes,(player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(-1.0, 1.0).normalized()), &mut nodes,(player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(1.0, -1.0).normalized()), &mut nodes,(player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Move(Vector2::new(1.0, 1.0).normalized()), &mut nodes,(player.score + 1) as i64);
-
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(0.0, 1.0)), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(0.0, -1.0)), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, 0.0)), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(1.0, 0.0)), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, -1.0).normalized()), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(-1.0, 1.0).normalized()), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(1.0, -1.0).normalized()), &mut nodes, (player.score + 1) as i64);
-                    run_simulation(&engine, &mut new_engine, &side, Intent::Dash(Vector2::new(1.0, 1.0).normalized()), &mut nodes, (player.score + 1) as i64);
-                }
-            }
-        };
-
-        let best : Vec<Node> = get_best(&nodes);
-        let mut cost = best[0].cost;
-        let mut intent = best[0].first_intent;
-        let mut rng = ::rand::thread_rng();
-        for i in best.iter() {
-            println!("Getting best intent");
-            println!("intent : {:?}", i.first_intent);
-            if i.cost < cost {
-                cost = i.cost;
-                intent = i.first_intent;
-            }
-            if i.cost == cost && rng.gen_range(1, 100) > 50 {
-                cost = i.cost;
-                intent = i.first_intent;
-            }
+        for intent in engine.legal_intents_with_throws(side, &self.throws, self.curved_throws) {
+            let score = match intent {
+                Intent::Throw(dir) => player.score as i64 + self.weights.throw_bonus(dir) / 100,
+                _ => player.score as i64 + self.weights.move_bonus,
+            };
+            run_simulation(engine, &side, intent, &mut nodes, score, self);
         }
 
-        intent
+        nodes
     }
 }
 
-pub struct TabularQLearningAgent {}
-pub const QVALUES_ACTIONS: usize = 17;
-pub type QValues = HashMap<u64, ([f32; QVALUES_ACTIONS], [f32; QVALUES_ACTIONS])>;
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum ActionResult {
-    None,
-    Moved,
-    Dashed,
-    GrabbedFrisbee,
-    Threw,
-}
-
-impl Agent for TabularQLearningAgent {
+impl Agent for DijkstraAgent {
     fn get_type(&self) -> AgentType {
-        AgentType::TabularQLearning
+        AgentType::Dijkstra
     }
     fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
-        let mut rng = ::rand::thread_rng();
-        let intent: HumanIntent;
-
-        fn max_index(array: &[f32; QVALUES_ACTIONS]) -> usize {
-            let mut idx = 0;
-
-            for (key, &value) in array.iter().enumerate() {
-                if value > array[idx] {
-                    idx = key;
-                }
-            }
+        self.act_ranked(side, engine).first().map_or(Intent::None, |&(intent, _)| intent)
+    }
+}
 
-            idx
+impl DijkstraAgent {
+    // Every distinct intent found by the search with the best score of its nodes, highest first and cheapest first among equal scores
+    // The first one is what `act` plays, drawn like `pick_cheapest` when several are tied
+    pub fn act_ranked(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Vec<(Intent, f64)> {
+        // Mid-dash there is nothing to choose from, only the root would be searched
+        if engine.legal_intents_with_throws(side, &self.throws, self.curved_throws).is_empty() {
+            return Vec::new();
         }
-
-        if rng.gen_range(0.0, 1.0) < engine.explo_rate {
-            // Explore
-            let intent_index = rng.gen_range(0, QVALUES_ACTIONS);
-            intent = human_intent_from_index(intent_index as u8);
-        } else {
-            // Exploit
-            let hash = engine.hash();
-            let intent_index = match side {
-                PlayerSide::Left => {
-                    if engine.q_values.contains_key(&hash) {
-                        max_index(&engine.q_values[&hash].0)
-                    } else {
-                        0
-                    }
-                },
-                PlayerSide::Right => {
-                    if engine.q_values.contains_key(&hash) {
-                        max_index(&engine.q_values[&hash].1)
-                    } else {
-                        0
+        let nodes = self.search(side, engine);
+        let best : Vec<Node> = get_best(&nodes);
+        self.diagnostics_log = self.diagnostics(&best);
+        // Drawn from the engine so the choice is reproducible when it is seeded
+        let chosen = pick_cheapest(&best, engine.rng_for(side));
+
+        let mut candidates: Vec<(Intent, i64, i64)> = Vec::new();
+        for node in nodes.iter() {
+            match candidates.iter_mut().find(|c| c.0 == node.first_intent) {
+                Some(c) => {
+                    if node.score > c.1 || (node.score == c.1 && node.cost < c.2) {
+                        c.1 = node.score;
+                        c.2 = node.cost;
                     }
                 },
+                None => candidates.push((node.first_intent, node.score, node.cost)),
             };
-            intent = human_intent_from_index(intent_index as u8);
         }
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.cmp(&b.2)));
+        // The chosen intent is one of the best and cheapest, it leads the others tied with it
+        if let Some(i) = candidates.iter().position(|c| c.0 == chosen) {
+            let c = candidates.remove(i);
+            candidates.insert(0, c);
+        }
+        candidates.into_iter().map(|(intent, score, _)| (intent, score as f64)).collect()
+    }
+}
 
-        match side {
-            PlayerSide::Left => {
-                engine.inputs.0 = intent;
-            },
-            PlayerSide::Right => {
-                engine.inputs.1 = intent;
+// First intent of one of the cheapest nodes among `best`, picked uniformly when several are tied
+pub fn pick_cheapest<R: Rng>(best: &[Node], rng: &mut R) -> Intent {
+    let cost = match best.iter().map(|node| node.cost).min() {
+        Some(cost) => cost,
+        None => return Intent::None,
+    };
+    let tied: Vec<&Node> = best.iter().filter(|node| node.cost == cost).collect();
+    rng.choose(&tied).map_or(Intent::None, |node| node.first_intent)
+}
+
+// Largest distance a player and the frisbee can close in one step: a dashing player moves 0.4 and the frisbee 0.25
+const ASTAR_MAX_CLOSING_SPEED: f64 = 0.65;
+const ASTAR_FRISBEE_STEP: f64 = 0.25;
+
+pub struct AStarSearch {
+    pub intent:       Intent,
+    pub expanded:     usize,
+    pub reached_goal: bool,
+}
+
+struct AStarNode {
+    engine:       GameEngine,
+    first_intent: Intent,
+    g:            i64,
+    h:            f64,
+}
+
+// Ordered so that `BinaryHeap` pops the lowest `g + h`, then the lowest `h` on ties
+#[derive(PartialEq)]
+struct AStarEntry {
+    f:     f64,
+    h:     f64,
+    index: usize,
+}
+
+impl Eq for AStarEntry {}
+
+impl PartialOrd for AStarEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AStarEntry {
+    fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+        other.f.partial_cmp(&self.f).unwrap_or(::std::cmp::Ordering::Equal)
+            .then_with(|| other.h.partial_cmp(&self.h).unwrap_or(::std::cmp::Ordering::Equal))
+    }
+}
+
+// Searches for the first intent leading to the goal: grabbing the frisbee, or scoring if the agent already holds it
+// `g` is the step cost Dijkstra uses and `h` the frisbee distance in steps, which never overestimates the remaining cost
+// States already expanded are skipped, so paths that only differ by the order of their moves are explored once
+pub struct AStarAgent {
+    pub max_expansions: u32,
+}
+
+impl AStarAgent {
+    pub fn new(max_expansions: u32) -> Self {
+        Self {
+            max_expansions,
+        }
+    }
+
+    pub fn search(&self, side: PlayerSide, engine: &GameEngine) -> AStarSearch {
+        use std::collections::BinaryHeap;
+
+        let holding = engine.frisbee.held_by_player == Some(side);
+        let score = side_score(engine, side);
+
+        let mut root = GameEngine::new();
+        engine.copy_in(&mut root);
+        let h = astar_heuristic(&root, side, holding);
+        let mut nodes = vec![AStarNode { engine: root, first_intent: Intent::None, g: 0, h }];
+        let mut open = BinaryHeap::new();
+        open.push(AStarEntry { f: h, h, index: 0 });
+
+        let mut expanded = 0;
+        let mut closest: Option<usize> = None;
+        let mut closed = HashSet::new();
+        while let Some(entry) = open.pop() {
+            if expanded >= self.max_expansions as usize {
+                break;
+            }
+
+            let current = entry.index;
+            if nodes[current].engine.state_of_game != StateOfGame::Playing {
+                continue;
+            }
+            // Moves commute, many paths reach the same state
+            if !closed.insert(astar_state_key(&nodes[current].engine)) {
+                continue;
+            }
+            expanded += 1;
+
+            let mut intents = nodes[current].engine.legal_intents(side);
+            if intents.is_empty() {
+                // Sliding, the player can only wait for the dash to end
+                intents.push(Intent::None);
+            }
+
+            for intent in intents {
+                let mut child = GameEngine::new();
+                nodes[current].engine.copy_in(&mut child);
+                child.step(intents_for(side, intent));
+
+                let cost = match intent {
+                    Intent::Dash(_) => 4,
+                    _ => 1,
+                };
+                let first_intent = if current == 0 { intent } else { nodes[current].first_intent };
+                let g = nodes[current].g + cost;
+
+                let grabbed = !holding && child.frisbee.held_by_player == Some(side);
+                if grabbed || side_score(&child, side) > score {
+                    return AStarSearch {
+                        intent: first_intent,
+                        expanded,
+                        reached_goal: true,
+                    };
+                }
+
+                let h = astar_heuristic(&child, side, holding);
+                let index = nodes.len();
+                nodes.push(AStarNode { engine: child, first_intent, g, h });
+                open.push(AStarEntry { f: g as f64 + h, h, index });
+
+                if closest.is_none_or(|c| h < nodes[c].h) {
+                    closest = Some(index);
+                }
+            }
+        }
+
+        // Out of budget, head towards the closest state found so far
+        AStarSearch {
+            intent: closest.map_or(Intent::None, |c| nodes[c].first_intent),
+            expanded,
+            reached_goal: false,
+        }
+    }
+}
+
+impl Agent for AStarAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::AStar
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        self.search(side, engine).intent
+    }
+}
+
+// States closer than the key's resolution are considered the same
+fn astar_state_key(engine: &GameEngine) -> (i64, i64, i64, i64, i64, i64, bool, bool, i8) {
+    fn key(value: f64) -> i64 {
+        (value * 20.0).round() as i64
+    }
+    (
+        key(engine.players.0.pos.x), key(engine.players.0.pos.y),
+        key(engine.players.1.pos.x), key(engine.players.1.pos.y),
+        key(engine.frisbee.pos.x), key(engine.frisbee.pos.y),
+        engine.players.0.slide.is_some(), engine.players.1.slide.is_some(),
+        ::player::player_side_to_i8(engine.frisbee.held_by_player),
+    )
+}
+
+fn side_score(engine: &GameEngine, side: PlayerSide) -> i8 {
+    match side {
+        PlayerSide::Left => engine.players.0.score,
+        PlayerSide::Right => engine.players.1.score,
+    }
+}
+
+fn astar_heuristic(engine: &GameEngine, side: PlayerSide, scoring: bool) -> f64 {
+    if scoring {
+        // The frisbee has to cross the opponent's goal line
+        let goal_x = match side {
+            PlayerSide::Left => engine.field.goal_line(),
+            PlayerSide::Right => -engine.field.goal_line(),
+        };
+        return (goal_x - engine.frisbee.pos.x).abs() / ASTAR_FRISBEE_STEP;
+    }
+
+    let player = match side {
+        PlayerSide::Left => &engine.players.0,
+        PlayerSide::Right => &engine.players.1,
+    };
+    // The frisbee is grabbed as soon as it is closer than 1.0
+    (engine.frisbee.pos.distance(&player.pos) - 1.0).max(0.0) / ASTAR_MAX_CLOSING_SPEED
+}
+
+// Runs to where the frisbee will be instead of where it is
+pub struct PredictiveCatchAgent {
+    pub horizon: u32, // Number of frames the trajectory is extrapolated over
+}
+
+impl PredictiveCatchAgent {
+    pub fn new(horizon: u32) -> Self {
+        Self {
+            horizon,
+        }
+    }
+
+    // Where the frisbee can be caught on the way, and whether getting there in time needs a dash
+    pub fn intercept(&self, side: PlayerSide, engine: &GameEngine) -> Option<(Vector2, bool)> {
+        let player = match side {
+            PlayerSide::Left => &engine.players.0,
+            PlayerSide::Right => &engine.players.1,
+        };
+
+        let mut frisbee = engine.frisbee;
+        let mut dash_target = None;
+        for frame in 1..=self.horizon {
+            ::collision::frisbee_move_substeps(&mut frisbee, &engine.field, engine.substeps);
+            if frisbee.pos.x.abs() >= engine.field.goal_line() {
+                // Stopped on the goal line, the frisbee is scored before anyone can reach it
+                break;
+            }
+
+            // The frisbee is grabbed as soon as it is closer than 1.0
+            let distance = player.pos.distance(&frisbee.pos) - 1.0;
+            if distance <= frame as f64 * player.config.move_speed * 0.1 {
+                return Some((frisbee.pos, false));
+            }
+            let dash_frames = frame.min(player.config.dash_duration_frames) as f64;
+            if dash_target.is_none() && player.can_dash() && distance <= (dash_frames * player.config.dash_speed * 0.1).min(PLAYER_DASH_POWER) {
+                dash_target = Some((frisbee.pos, true));
+            }
+        }
+        dash_target
+    }
+}
+
+impl Agent for PredictiveCatchAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::PredictiveCatch
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let player = match side {
+            PlayerSide::Left => &engine.players.0,
+            PlayerSide::Right => &engine.players.1,
+        };
+
+        if engine.frisbee.held_by_player == Some(side) {
+            return Intent::Throw(ThrowDirection::Middle);
+        }
+        if player.slide.is_some() {
+            return Intent::None;
+        }
+
+        let in_flight = engine.frisbee.held_by_player.is_none() && engine.frisbee.speed != 0.0;
+        let catchable = engine.frisbee.last_held != Some(side);
+        if in_flight && catchable {
+            if let Some((target, dash)) = self.intercept(side, engine) {
+                let dir = (target - player.pos).normalized();
+                return if dash { Intent::Dash(dir) } else { Intent::Move(dir) };
+            }
+        }
+
+        let dir = (engine.frisbee.pos - player.pos).normalized();
+        Intent::Move(dir)
+    }
+}
+
+// Farther than this from the frisbee the seeking agent dashes toward it
+pub const SEEK_DASH_DISTANCE: f64 = PLAYER_DASH_POWER;
+
+// Baseline for the learners: heads straight for the frisbee and throws it straight at the opponent's goal
+pub struct SeekAgent {}
+
+impl Agent for SeekAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::Seek
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let player = match side {
+            PlayerSide::Left => &engine.players.0,
+            PlayerSide::Right => &engine.players.1,
+        };
+
+        if engine.frisbee.held_by_player == Some(side) {
+            return Intent::Throw(ThrowDirection::Middle);
+        }
+        if player.slide.is_some() {
+            return Intent::None;
+        }
+
+        let to_frisbee = engine.frisbee.pos - player.pos;
+        let dir = to_frisbee.normalized();
+        if to_frisbee.length() > SEEK_DASH_DISTANCE && player.can_dash() {
+            Intent::Dash(dir)
+        } else {
+            Intent::Move(dir)
+        }
+    }
+}
+
+// Curriculum opponent: plays the agent of the last stage started, each stage starting on the given episode
+// Episodes are read from the engine's counter, starting from the one the agent first acted in
+pub struct DifficultyAgent {
+    pub stages:    Vec<(u32, Box<dyn Agent>)>, // First episode of each agent, in increasing order, the first one should be 0
+    episode:       u32,
+    first_episode: Option<u32>,
+}
+
+impl DifficultyAgent {
+    pub fn new(stages: Vec<(u32, Box<dyn Agent>)>) -> Self {
+        assert!(!stages.is_empty(), "A difficulty agent needs at least one stage.");
+        Self {
+            stages,
+            episode: 0,
+            first_episode: None,
+        }
+    }
+
+    // Episodes since the one the agent first acted in, as of its last action
+    pub fn episode(&self) -> u32 {
+        self.episode
+    }
+
+    // Index of the stage played on the current episode
+    pub fn stage(&self) -> usize {
+        self.stages.iter().rposition(|&(start, _)| start <= self.episode).unwrap_or(0)
+    }
+}
+
+impl Agent for DifficultyAgent {
+    fn get_type(&self) -> AgentType {
+        self.stages[self.stage()].1.get_type()
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let first = *self.first_episode.get_or_insert(engine.episode);
+        self.episode = engine.episode.saturating_sub(first);
+
+        let stage = self.stage();
+        self.stages[stage].1.act(side, engine)
+    }
+    // Ends the episode of the stage that played it
+    fn end_episode(&mut self, side: PlayerSide, engine: &mut GameEngine) {
+        let stage = self.stage();
+        self.stages[stage].1.end_episode(side, engine)
+    }
+    fn reseed(&mut self, seed: u64) {
+        for stage in self.stages.iter_mut() {
+            stage.1.reseed(seed);
+        }
+    }
+}
+
+// Profiling wrapper, records how long each decision of `agent` takes
+pub struct TimedAgent<A: Agent> {
+    pub agent:   A,
+    pub samples: Vec<Duration>,
+}
+
+impl<A: Agent> TimedAgent<A> {
+    pub fn new(agent: A) -> Self {
+        Self {
+            agent,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.samples.len() {
+            0 => Duration::from_secs(0),
+            n => self.samples.iter().sum::<Duration>() / n as u32,
+        }
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples.iter().cloned().max().unwrap_or_default()
+    }
+
+    // The smallest duration at least 99% of the decisions didn't exceed
+    pub fn p99(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::from_secs(0);
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let rank = (sorted.len() as f64 * 0.99).ceil() as usize;
+        sorted[rank.max(1) - 1]
+    }
+}
+
+impl<A: Agent> Agent for TimedAgent<A> {
+    fn get_type(&self) -> AgentType {
+        self.agent.get_type()
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let start = Instant::now();
+        let intent = self.agent.act(side, engine);
+        self.samples.push(start.elapsed());
+        intent
+    }
+    fn end_episode(&mut self, side: PlayerSide, engine: &mut GameEngine) {
+        self.agent.end_episode(side, engine)
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.agent.reseed(seed)
+    }
+}
+
+pub struct TabularQLearningAgent {
+    pub canonical: bool, // Looks states up with `GameEngine::hash_canonical`, playing the right side from the left table
+    pub n_step:    usize, // Learns online from n-step returns when not 0, otherwise the table is left to the trainer
+    pub previous:  Option<(u64, usize)>, // State and action picked on the previous frame, rewarded by the step after it
+    pub buffer:    NStepBuffer,
+    pub shared:    Option<SharedQValues>, // Read and updated instead of the engine's table when set
+    pub temperature: f32, // Of the Boltzmann exploration, see `GameEngine::exploration`
+}
+
+impl TabularQLearningAgent {
+    pub fn new() -> Self {
+        Self::with_n_step(0)
+    }
+
+    pub fn with_n_step(n_step: usize) -> Self {
+        Self {
+            canonical: false,
+            n_step,
+            previous: None,
+            buffer: NStepBuffer::new(n_step),
+            shared: None,
+            temperature: 1.0,
+        }
+    }
+
+    // Learns in a table shared with the agents of other engines, like self-play workers on other threads
+    pub fn with_shared(n_step: usize, shared: SharedQValues) -> Self {
+        Self {
+            shared: Some(shared),
+            ..Self::with_n_step(n_step)
+        }
+    }
+}
+
+impl Default for TabularQLearningAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The last `n` transitions as (state, action, reward), the oldest one is updated toward
+// r0 + gamma * r1 + ... + gamma^(n - 1) * r(n-1) + gamma^n * max Q(sn, .) once `n` are buffered
+pub struct NStepBuffer {
+    pub n:           usize,
+    pub transitions: VecDeque<(u64, usize, f32)>,
+}
+
+impl NStepBuffer {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            transitions: VecDeque::with_capacity(n),
+        }
+    }
+
+    // Discounted sum of the buffered rewards
+    fn rewards_return(&self, discount_factor: f32) -> f32 {
+        self.transitions.iter().rev().fold(0.0, |acc, &(_, _, reward)| reward + discount_factor * acc)
+    }
+
+    fn update_oldest(&mut self, q_values: &mut QValues, side: PlayerSide, target: f32, learning_rate: f32) {
+        if let Some((state, action, _)) = self.transitions.pop_front() {
+            let values = side_q_values_mut(q_values, state, side);
+            values[action] += learning_rate * (target - values[action]);
+        }
+    }
+
+    // `next_state` is where the transition led to, the n-step return bootstraps from it
+    #[allow(clippy::too_many_arguments)]
+    pub fn push(&mut self, q_values: &mut QValues, side: PlayerSide, state: u64, action: usize, reward: f32, next_state: u64, learning_rate: f32, discount_factor: f32) {
+        self.transitions.push_back((state, action, reward));
+        if self.transitions.len() >= self.n.max(1) {
+            let next_max = match side_q_values(q_values, next_state, side) {
+                Some(values) => values.iter().cloned().fold(f32::MIN, f32::max),
+                None => 0.0,
+            };
+            let target = self.rewards_return(discount_factor) + discount_factor.powi(self.transitions.len() as i32) * next_max;
+            self.update_oldest(q_values, side, target, learning_rate);
+        }
+    }
+
+    // At the end of an episode there is nothing to bootstrap from, every transition gets its truncated return
+    pub fn flush(&mut self, q_values: &mut QValues, side: PlayerSide, learning_rate: f32, discount_factor: f32) {
+        while !self.transitions.is_empty() {
+            let target = self.rewards_return(discount_factor);
+            self.update_oldest(q_values, side, target, learning_rate);
+        }
+    }
+}
+
+fn side_reward(engine: &GameEngine, side: PlayerSide) -> f32 {
+    match side {
+        PlayerSide::Left => engine.rewards.0,
+        PlayerSide::Right => engine.rewards.1,
+    }
+}
+pub const QVALUES_ACTIONS: usize = ActionSpace::len();
+// Number of distinct states told apart by `GameEngine::hash`, every hash is below it
+// 9 * 11 player positions, 19 * 11 frisbee positions, 2 for the last holder, 5 frisbee angles and 3 headings
+pub const QVALUES_MAX_STATES: u64 = 620730;
+pub type QValues = HashMap<u64, ([f32; QVALUES_ACTIONS], [f32; QVALUES_ACTIONS])>;
+// A table several threads can learn in, readers only wait for the updates
+pub type SharedQValues = Arc<RwLock<QValues>>;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActionResult {
+    None,
+    Moved,
+    Dashed,
+    GrabbedFrisbee,
+    Threw,
+}
+
+// Indices of the largest values, in increasing order
+pub fn max_indices(array: &[f32; QVALUES_ACTIONS]) -> Vec<usize> {
+    let max = array.iter().cloned().fold(f32::MIN, f32::max);
+    (0..QVALUES_ACTIONS).filter(|&i| array[i] == max).collect()
+}
+
+// Ties are broken uniformly so the greedy policy isn't biased toward the lowest indices
+pub fn max_index<R: Rng>(array: &[f32; QVALUES_ACTIONS], rng: &mut R) -> usize {
+    *rng.choose(&max_indices(array)).unwrap()
+}
+
+pub fn side_q_values(q_values: &QValues, hash: u64, side: PlayerSide) -> Option<&[f32; QVALUES_ACTIONS]> {
+    match q_values.get(&hash) {
+        Some(values) => match side {
+            PlayerSide::Left => Some(&values.0),
+            PlayerSide::Right => Some(&values.1),
+        },
+        None => None,
+    }
+}
+
+pub fn side_q_values_mut(q_values: &mut QValues, hash: u64, side: PlayerSide) -> &mut [f32; QVALUES_ACTIONS] {
+    let values = q_values.entry(hash).or_insert(([0.0; QVALUES_ACTIONS], [0.0; QVALUES_ACTIONS]));
+    match side {
+        PlayerSide::Left => &mut values.0,
+        PlayerSide::Right => &mut values.1,
+    }
+}
+
+// Samples an action with a probability proportional to exp(Q / temperature), greedily when the temperature isn't positive
+// The greatest value is subtracted first so that low temperatures don't overflow
+pub fn boltzmann_index<R: Rng>(values: &[f32; QVALUES_ACTIONS], temperature: f32, rng: &mut R) -> usize {
+    if temperature <= 0.0 {
+        return max_index(values, rng);
+    }
+    let max = values.iter().cloned().fold(f32::MIN, f32::max);
+    let weights: Vec<f32> = values.iter().map(|value| ((value - max) / temperature).exp()).collect();
+    let total: f32 = weights.iter().sum();
+    let mut rand = rng.gen_range(0.0, total);
+    for (i, weight) in weights.iter().enumerate() {
+        if rand < *weight {
+            return i;
+        }
+        rand -= weight;
+    }
+    // Rounding can leave `rand` just past the last weight
+    weights.iter().rposition(|&w| w > 0.0).unwrap()
+}
+
+// Returns true if the learner should explore on this frame, never in evaluation mode
+fn explores(engine: &mut GameEngine, side: PlayerSide) -> bool {
+    let explo_rate = engine.explo_rate;
+    !engine.eval_mode && engine.rng_for(side).gen_range(0.0, 1.0) < explo_rate
+}
+
+// Epsilon-greedy selection of an action index, shared by the tabular learners
+// `table_side` is the side of the table read, the draws come from the stream of the acting `side`
+fn epsilon_greedy_index(engine: &mut GameEngine, side: PlayerSide, table_side: PlayerSide, hash: u64) -> usize {
+    let values = side_q_values(&engine.q_values, hash, table_side).cloned();
+    epsilon_greedy_index_of(engine, side, values)
+}
+
+// Same as `epsilon_greedy_index` with the values of the state already read, None if it was never visited
+fn epsilon_greedy_index_of(engine: &mut GameEngine, side: PlayerSide, values: Option<[f32; QVALUES_ACTIONS]>) -> usize {
+    if explores(engine, side) {
+        // Explore
+        engine.rng_for(side).gen_range(0, QVALUES_ACTIONS)
+    } else {
+        // Exploit
+        match values {
+            Some(values) => max_index(&values, engine.rng_for(side)),
+            // Every action is worth zero in a state never visited
+            None => engine.rng_for(side).gen_range(0, QVALUES_ACTIONS),
+        }
+    }
+}
+
+fn apply_q_intent(engine: &mut GameEngine, side: PlayerSide, intent_index: usize) -> Intent {
+    let intent = ActionSpace::to_human_intent(intent_index);
+
+    match side {
+        PlayerSide::Left => {
+            engine.inputs.0 = intent;
+        },
+        PlayerSide::Right => {
+            engine.inputs.1 = intent;
+        },
+    };
+
+    human_intent_to_intent(engine, intent, side)
+}
+
+pub fn uses_q_values(agent_type: AgentType) -> bool {
+    matches!(agent_type, AgentType::TabularQLearning | AgentType::Sarsa | AgentType::DoubleQLearning)
+}
+
+impl Agent for TabularQLearningAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::TabularQLearning
+    }
+    // Credits the transitions still buffered with what is left of their returns
+    fn end_episode(&mut self, side: PlayerSide, engine: &mut GameEngine) {
+        if let Some((state, action)) = self.previous.take() {
+            if self.n_step > 0 && !engine.eval_mode {
+                let reward = side_reward(engine, side);
+                let table_side = if self.canonical { PlayerSide::Left } else { side };
+                let (learning_rate, discount_factor) = (engine.learning_rate, engine.discount_factor);
+                self.buffer.transitions.push_back((state, action, reward));
+                match self.shared {
+                    Some(ref shared) => self.buffer.flush(&mut shared.write().unwrap(), table_side, learning_rate, discount_factor),
+                    None => self.buffer.flush(&mut engine.q_values, table_side, learning_rate, discount_factor),
+                };
+            }
+        }
+        self.buffer.transitions.clear();
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        // The right player plays from the left table on the mirrored field
+        let mirrored = self.canonical && side == PlayerSide::Right;
+        let (table_side, hash) = if mirrored {
+            (PlayerSide::Left, engine.hash_canonical(side))
+        } else {
+            (side, engine.hash())
+        };
+
+        let learns = self.n_step > 0 && !engine.eval_mode;
+        if learns {
+            if let Some((state, action)) = self.previous {
+                let reward = side_reward(engine, side);
+                let (learning_rate, discount_factor) = (engine.learning_rate, engine.discount_factor);
+                match self.shared {
+                    Some(ref shared) => self.buffer.push(&mut shared.write().unwrap(), table_side, state, action, reward, hash, learning_rate, discount_factor),
+                    None => self.buffer.push(&mut engine.q_values, table_side, state, action, reward, hash, learning_rate, discount_factor),
+                };
+            }
+        }
+
+        let values = match self.shared {
+            Some(ref shared) => side_q_values(&shared.read().unwrap(), hash, table_side).cloned(),
+            None => side_q_values(&engine.q_values, hash, table_side).cloned(),
+        };
+        let intent_index = match engine.exploration {
+            ExplorationStrategy::Boltzmann if !engine.eval_mode => {
+                // Every action is worth zero in a state never visited
+                let values = values.unwrap_or([0.0; QVALUES_ACTIONS]);
+                boltzmann_index(&values, self.temperature, engine.rng_for(side))
             },
+            _ => epsilon_greedy_index_of(engine, side, values),
+        };
+        if learns {
+            self.previous = Some((hash, intent_index));
+        }
+        let intent_index = if mirrored {
+            ActionSpace::from_human_intent(mirror_human_intent(ActionSpace::to_human_intent(intent_index)))
+        } else {
+            intent_index
         };
+        apply_q_intent(engine, side, intent_index)
+    }
+}
 
-        human_intent_to_intent(engine, intent, side)
+// Same input with left and right swapped
+pub fn mirror_human_intent(intent: HumanIntent) -> HumanIntent {
+    let mut mirrored = intent - (HumanIntent::LEFT | HumanIntent::RIGHT);
+    if intent.contains(HumanIntent::LEFT) {
+        mirrored |= HumanIntent::RIGHT;
     }
+    if intent.contains(HumanIntent::RIGHT) {
+        mirrored |= HumanIntent::LEFT;
+    }
+    mirrored
 }
 
+// Keeps two tables to reduce the overestimation of the max in Q-learning, see `GameEngine::update_double_q_value`
+// Actions are picked on the sum of both tables
+pub struct DoubleQLearningAgent {}
+
+impl Agent for DoubleQLearningAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::DoubleQLearning
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let hash = engine.hash();
+        let intent_index = if explores(engine, side) {
+            engine.rng_for(side).gen_range(0, QVALUES_ACTIONS)
+        } else {
+            let values = double_q_values(engine, hash, side);
+            max_index(&values, engine.rng_for(side))
+        };
+        apply_q_intent(engine, side, intent_index)
+    }
+}
+
+pub fn double_q_values(engine: &GameEngine, hash: u64, side: PlayerSide) -> [f32; QVALUES_ACTIONS] {
+    let mut sum = [0.0; QVALUES_ACTIONS];
+    for table in [&engine.q_values, &engine.q_values_b].iter() {
+        if let Some(values) = side_q_values(table, hash, side) {
+            for (total, value) in sum.iter_mut().zip(values.iter()) {
+                *total += value;
+            }
+        }
+    }
+    sum
+}
+
+// On-policy counterpart of the Q-learner: Q(s, a) moves toward r + gamma * Q(s', a')
+// where a' is the action actually picked in s', the update happens when a' is chosen
+pub struct SarsaAgent {
+    pub previous: Option<(u64, usize)>,
+}
+
+impl SarsaAgent {
+    pub fn new() -> Self {
+        Self {
+            previous: None,
+        }
+    }
+}
+
+impl Default for SarsaAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn sarsa_update(q_values: &mut QValues, side: PlayerSide, state: u64, action: usize, reward: f32, next_state: u64, next_action: usize, learning_rate: f32, discount_factor: f32) {
+    let next_value = match side_q_values(q_values, next_state, side) {
+        Some(values) => values[next_action],
+        None => 0.0,
+    };
+    let values = side_q_values_mut(q_values, state, side);
+    values[action] += learning_rate * (reward + discount_factor * next_value - values[action]);
+}
+
+impl Agent for SarsaAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::Sarsa
+    }
+    // Nothing follows the last action, its value moves toward the reward alone
+    fn end_episode(&mut self, side: PlayerSide, engine: &mut GameEngine) {
+        if let Some((state, action)) = self.previous.take() {
+            if !engine.eval_mode {
+                let reward = side_reward(engine, side);
+                let learning_rate = engine.learning_rate;
+                let values = side_q_values_mut(&mut engine.q_values, state, side);
+                values[action] += learning_rate * (reward - values[action]);
+            }
+        }
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let hash = engine.hash();
+        let intent_index = epsilon_greedy_index(engine, side, side, hash);
+        if engine.eval_mode {
+            self.previous = None;
+            return apply_q_intent(engine, side, intent_index);
+        }
+
+        if let Some((state, action)) = self.previous {
+            // The rewards were set by the step that followed the previous action
+            let reward = match side {
+                PlayerSide::Left => engine.rewards.0,
+                PlayerSide::Right => engine.rewards.1,
+            };
+            let (learning_rate, discount_factor) = (engine.learning_rate, engine.discount_factor);
+            sarsa_update(&mut engine.q_values, side, state, action, reward, hash, intent_index, learning_rate, discount_factor);
+        }
+        self.previous = Some((hash, intent_index));
+
+        apply_q_intent(engine, side, intent_index)
+    }
+}
+
+// States are only inserted when they are first updated, a missing state reads as all zeros
+// Reserving all `QVALUES_MAX_STATES` states would cost tens of megabytes
+// while a match only visits a small fraction of them
 pub fn get_blank_q_values() -> QValues {
-    let size: u64 = 206909; // This is the `max_value` from GameEngine::hash()
-    let mut map = QValues::with_capacity(size as usize);
+    QValues::with_capacity(1024)
+}
+
+fn other_side(side: PlayerSide) -> PlayerSide {
+    match side {
+        PlayerSide::Left => PlayerSide::Right,
+        PlayerSide::Right => PlayerSide::Left,
+    }
+}
+
+fn intents_for(side: PlayerSide, intent: Intent) -> (Intent, Intent) {
+    match side {
+        PlayerSide::Left => (intent, Intent::None),
+        PlayerSide::Right => (Intent::None, intent),
+    }
+}
+
+pub type Evaluation = Box<dyn Fn(&GameEngine, PlayerSide) -> f64 + Send>;
+
+pub const EVAL_SCORE_WEIGHT: f64 = 10000.0; // Per point of difference
+pub const EVAL_POSSESSION_WEIGHT: f64 = 100000.0;
+pub const EVAL_DISTANCE_WEIGHT: f64 = 10000.0; // Per unit between the player and the frisbee
+
+// How good the state is for `side`, shared by the search agents
+// Rewards leading the score and holding the frisbee, and penalizes being far from it
+pub fn evaluate_state(engine: &GameEngine, side: PlayerSide) -> f64 {
+    score_difference(engine, side) * EVAL_SCORE_WEIGHT
+        + possession(engine, side) * EVAL_POSSESSION_WEIGHT
+        - frisbee_distance(engine, side) * EVAL_DISTANCE_WEIGHT
+}
+
+// 1 when `side` holds the frisbee, -1 when the opponent does
+fn possession(engine: &GameEngine, side: PlayerSide) -> f64 {
+    match engine.frisbee.held_by_player {
+        Some(held_by) if held_by == side => 1.0,
+        Some(_) => -1.0,
+        None => 0.0,
+    }
+}
+
+pub fn score_difference(engine: &GameEngine, side: PlayerSide) -> f64 {
+    let (own, opponent) = match side {
+        PlayerSide::Left => (engine.players.0.score, engine.players.1.score),
+        PlayerSide::Right => (engine.players.1.score, engine.players.0.score),
+    };
+    own as f64 - opponent as f64
+}
+
+pub struct MinimaxAgent {
+    pub depth:    u8,
+    pub evaluate: Evaluation,
+}
+
+impl MinimaxAgent {
+    pub fn new(depth: u8) -> Self {
+        Self::with_evaluation(depth, Box::new(score_difference))
+    }
+
+    pub fn with_evaluation(depth: u8, evaluate: Evaluation) -> Self {
+        Self {
+            depth,
+            evaluate,
+        }
+    }
+
+    // Each ply only one side acts, the other one is fed `Intent::None`
+    fn minimax(&self, engine: &GameEngine, side: PlayerSide, moving: PlayerSide, depth: u8, mut alpha: f64, mut beta: f64) -> f64 {
+        if depth == 0 || engine.state_of_game != StateOfGame::Playing {
+            return (self.evaluate)(engine, side);
+        }
+
+        let mut intents = engine.legal_intents(moving);
+        if intents.is_empty() {
+            // The moving side is dashing, it can only wait
+            intents.push(Intent::None);
+        }
+
+        let maximizing = moving == side;
+        let mut best = if maximizing { f64::NEG_INFINITY } else { f64::INFINITY };
+        let mut child = GameEngine::new();
+        for intent in intents {
+            engine.copy_in(&mut child);
+            child.step(intents_for(moving, intent));
+            let value = self.minimax(&child, side, other_side(moving), depth - 1, alpha, beta);
+
+            if maximizing {
+                best = best.max(value);
+                alpha = alpha.max(best);
+            } else {
+                best = best.min(value);
+                beta = beta.min(best);
+            }
+            if beta <= alpha {
+                break;
+            }
+        }
+        best
+    }
+}
 
-    for i in 0..size {
-        map.insert(i, ([0.0; QVALUES_ACTIONS], [0.0; QVALUES_ACTIONS]));
+impl Agent for MinimaxAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::Minimax
     }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let mut best = (f64::NEG_INFINITY, Intent::None);
+        let mut child = GameEngine::new();
+        for intent in engine.legal_intents(side) {
+            engine.copy_in(&mut child);
+            child.step(intents_for(side, intent));
+            let value = self.minimax(&child, side, other_side(side), self.depth.saturating_sub(1), best.0, f64::INFINITY);
+            if value > best.0 {
+                best = (value, intent);
+            }
+        }
+        best.1
+    }
+}
 
-    map
+struct MCTSNode {
+    engine:       GameEngine,
+    first_intent: Intent,
+    parent:       Option<usize>,
+    children:     Vec<usize>,
+    untried:      Vec<Intent>,
+    visits:       u32,
+    total:        f64,
+}
+
+pub struct MCTSAgent {
+    pub iterations:     u32,
+    pub c:              f64,
+    pub playout_frames: u32,
+    pub rng:            SmallRng,
+}
+
+impl MCTSAgent {
+    pub fn new(iterations: u32, c: f64, seed: u64) -> Self {
+        Self {
+            iterations,
+            c,
+            playout_frames: 60,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    fn new_node(&self, engine: &GameEngine, side: PlayerSide, first_intent: Intent, parent: Option<usize>) -> MCTSNode {
+        let mut node_engine = GameEngine::new();
+        engine.copy_in(&mut node_engine);
+        let untried = if engine.state_of_game == StateOfGame::Playing {
+            engine.legal_intents(side)
+        } else {
+            Vec::new()
+        };
+        MCTSNode {
+            engine: node_engine,
+            first_intent,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            total: 0.0,
+        }
+    }
+
+    fn ucb1(&self, node: &MCTSNode, parent_visits: u32) -> f64 {
+        if node.visits == 0 {
+            return f64::INFINITY;
+        }
+        let mean = node.total / node.visits as f64;
+        mean + self.c * ((parent_visits as f64).ln() / node.visits as f64).sqrt()
+    }
+
+    // Plays random intents for both sides, drawn from the agent's own RNG so the result only depends on its seed
+    fn playout(&mut self, engine: &mut GameEngine) {
+        for _ in 0..self.playout_frames {
+            if engine.state_of_game != StateOfGame::Playing {
+                break;
+            }
+            let left = self.random_intent(engine, PlayerSide::Left);
+            let right = self.random_intent(engine, PlayerSide::Right);
+            engine.step((left, right));
+        }
+    }
+
+    fn random_intent(&mut self, engine: &GameEngine, side: PlayerSide) -> Intent {
+        match self.rng.choose(&engine.legal_intents(side)) {
+            Some(intent) => *intent,
+            None => Intent::None,
+        }
+    }
+}
+
+impl Agent for MCTSAgent {
+    fn get_type(&self) -> AgentType {
+        AgentType::MCTS
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.rng = SmallRng::seed_from_u64(seed);
+    }
+    fn act(&mut self, side: PlayerSide, engine: &mut GameEngine) -> Intent {
+        let initial_score = score_difference(engine, side);
+        let mut nodes = vec![self.new_node(engine, side, Intent::None, None)];
+        let mut playout_engine = GameEngine::new();
+
+        for _ in 0..self.iterations {
+            // Selection
+            let mut current = 0;
+            while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+                let parent_visits = nodes[current].visits;
+                let mut best = (f64::NEG_INFINITY, nodes[current].children[0]);
+                for &child in nodes[current].children.iter() {
+                    let value = self.ucb1(&nodes[child], parent_visits);
+                    if value > best.0 {
+                        best = (value, child);
+                    }
+                }
+                current = best.1;
+            }
+
+            // Expansion
+            if !nodes[current].untried.is_empty() {
+                let index = self.rng.gen_range(0, nodes[current].untried.len());
+                let intent = nodes[current].untried.swap_remove(index);
+                let first_intent = if current == 0 { intent } else { nodes[current].first_intent };
+
+                let mut child_engine = GameEngine::new();
+                nodes[current].engine.copy_in(&mut child_engine);
+                child_engine.step(intents_for(side, intent));
+
+                let child = self.new_node(&child_engine, side, first_intent, Some(current));
+                nodes.push(child);
+                let child = nodes.len() - 1;
+                nodes[current].children.push(child);
+                current = child;
+            }
+
+            // Simulation
+            nodes[current].engine.copy_in(&mut playout_engine);
+            self.playout(&mut playout_engine);
+            let reward = score_difference(&playout_engine, side) - initial_score;
+
+            // Backpropagation
+            let mut node = Some(current);
+            while let Some(index) = node {
+                nodes[index].visits += 1;
+                nodes[index].total += reward;
+                node = nodes[index].parent;
+            }
+        }
+
+        let mut best: Option<&MCTSNode> = None;
+        for &child in nodes[0].children.iter() {
+            match best {
+                Some(node) if node.visits >= nodes[child].visits => {},
+                _ => best = Some(&nodes[child]),
+            };
+        }
+        match best {
+            Some(node) => node.first_intent,
+            None => Intent::None,
+        }
+    }
 }
@@ -1,92 +1,175 @@
 use frisbee::Frisbee;
 use vector2::Vector2;
 use player::{ Player, PlayerSide };
+use game_engine::{ ScoringZone, FieldConfig };
 
-struct Circle {
-    pub center: Vector2,
-    pub radius: f64
-}
+// Players are discs this wide, two of them closer than twice it overlap
+pub const PLAYER_RADIUS: f64 = 0.5;
+// Players never cross this close to the middle line, so the two of them can't overlap
+pub const NET: f64 = 0.75;
 
-pub fn player_collision(player: &mut Player) -> bool {
+// Keeps the player inside its half of the field, no closer than `net` to the middle line
+pub fn player_collision(player: &mut Player, field: &FieldConfig, net: f64) -> bool {
     let side = player.get_horizontal_position();
     let mut collided = false;
-    const WALL_EXT: f64 = 9.4;
-    const NET: f64 = 0.75;
-    const WALL_VERTICAL: f64 = 4.4;
+    let wall_ext = field.back_wall();
+    let wall_vertical = field.side_wall();
 
     if side > 0.0 {
-        if player.pos.x > WALL_EXT {
-            player.pos.x = WALL_EXT;
+        if player.pos.x > wall_ext {
+            player.pos.x = wall_ext;
             collided = true;
         }
-        if player.pos.x < NET {
-            player.pos.x = NET;
+        if player.pos.x < net {
+            player.pos.x = net;
             collided = true;
         }
     }
     if side < 0.0 {
-        if player.pos.x < -WALL_EXT {
-            player.pos.x = -WALL_EXT;
+        if player.pos.x < -wall_ext {
+            player.pos.x = -wall_ext;
             collided = true;
         }
-        if player.pos.x > -NET {
-            player.pos.x = -NET;
+        if player.pos.x > -net {
+            player.pos.x = -net;
             collided = true;
         }
     }
-    if player.pos.y > WALL_VERTICAL {
-        player.pos.y = WALL_VERTICAL;
+    if player.pos.y > wall_vertical {
+        player.pos.y = wall_vertical;
         collided = true;
     }
-    if player.pos.y < -WALL_VERTICAL {
-        player.pos.y = -WALL_VERTICAL;
+    if player.pos.y < -wall_vertical {
+        player.pos.y = -wall_vertical;
         collided = true;
     }
 
     collided
 }
 
-pub fn frisbee_collision_wall(frisbee: &mut Frisbee) {
-    const WALL_VERTICAL: f64 = 4.4;
+// Pushes overlapping players apart along the line between them, each by half the overlap
+// Players standing on the same spot are pushed back toward their own goals
+// Returns true if they overlapped
+pub fn separate_players(players: &mut (Player, Player)) -> bool {
+    let separation = players.1.pos - players.0.pos;
+    let distance = separation.length();
+    let overlap = 2.0 * PLAYER_RADIUS - distance;
+    if overlap <= 0.0 {
+        return false;
+    }
+    let direction = if distance > 0.0 { separation * (1.0 / distance) } else { Vector2::new(1.0, 0.0) };
+    players.0.pos -= direction * (overlap / 2.0);
+    players.1.pos += direction * (overlap / 2.0);
+    true
+}
+
+// Moves the frisbee for one step, slowed down by its drag, reflecting it off every wall crossed on the way rather than only testing where
+// it ends up, so a fast frisbee can't tunnel through a wall or a corner
+// Stops on the goal line when crossing it inside the goal, `frisbee_collision_goal` then scores
+// `dt` is the fraction of a step to move for, see `frisbee_move_substeps`
+// Returns true if the frisbee bounced
+pub fn frisbee_move(frisbee: &mut Frisbee, field: &FieldConfig, dt: f64) -> bool {
+    let wall_vertical = field.side_wall();
+    let goal_line = field.goal_line();
 
-    // TODO: check rebound angles
-    if frisbee.pos.y >= WALL_VERTICAL || frisbee.pos.y <= -WALL_VERTICAL {
-        frisbee.direction.y *= -1.0;
-        if frisbee.pos.y >= WALL_VERTICAL {
-            frisbee.direction = Vector2::new(frisbee.direction.x, -(frisbee.direction.y.abs())).normalized();
+    // Time of impact along `movement`, in [0, 1], if `pos + movement` crosses `limit` on either side
+    fn impact(pos: f64, movement: f64, limit: f64) -> Option<f64> {
+        if movement > 0.0 && pos + movement > limit {
+            Some(((limit - pos) / movement).max(0.0))
+        } else if movement < 0.0 && pos + movement < -limit {
+            Some(((-limit - pos) / movement).max(0.0))
+        } else {
+            None
         }
+    }
 
-        if frisbee.pos.y <= -WALL_VERTICAL {
-            frisbee.direction = Vector2::new(frisbee.direction.x, frisbee.direction.y.abs()).normalized();
-        }
+    let mut movement = frisbee.velocity() * dt;
+    let mut bounced = false;
+    // A step can cross at most a side wall and a back wall, or both side walls, more than that is a degenerate case
+    for _ in 0..4 {
+        let hit_y = impact(frisbee.pos.y, movement.y, wall_vertical);
+        let hit_x = impact(frisbee.pos.x, movement.x, goal_line);
+        let t = match (hit_x, hit_y) {
+            (Some(tx), Some(ty)) => tx.min(ty),
+            (Some(t), None) | (None, Some(t)) => t,
+            (None, None) => {
+                frisbee.pos += movement;
+                movement = Vector2::zero();
+                break;
+            }
+        };
+
+        frisbee.pos += movement * t;
+        movement *= 1.0 - t;
 
-        if frisbee.direction.y == 0.0 {
-            if frisbee.pos.y >= WALL_VERTICAL {
-                frisbee.pos.y = WALL_VERTICAL - 0.001;
-            } else if frisbee.pos.y <= -WALL_VERTICAL {
-                frisbee.pos.y = -WALL_VERTICAL + 0.001;
+        // Both walls are reflected at once when hitting exactly in a corner
+        if hit_x == Some(t) {
+            frisbee.pos.x = goal_line * frisbee.pos.x.signum();
+            if field.in_goal(frisbee.pos.y) {
+                movement = Vector2::zero();
+                break;
             }
+            movement.x = -movement.x;
+            frisbee.direction.x = -frisbee.direction.x;
+            bounced = true;
+        }
+        if hit_y == Some(t) {
+            frisbee.pos.y = wall_vertical * frisbee.pos.y.signum();
+            movement.y = -movement.y;
+            frisbee.direction.y = -frisbee.direction.y;
+            bounced = true;
         }
     }
+    if !movement.approx_eq(&Vector2::zero(), 1e-9) {
+        // Give up on the rest of the step, clamping inside the field
+        frisbee.pos.x = frisbee.pos.x.clamp(-goal_line, goal_line);
+        frisbee.pos.y = frisbee.pos.y.clamp(-wall_vertical, wall_vertical);
+    }
+
+    frisbee.apply_curve(dt);
+    frisbee.apply_drag(dt);
+    bounced
+}
+
+// Moves the frisbee for one step split in `substeps` equal moves, curving and slowing down between them
+// Returns true if the frisbee bounced in any of them
+pub fn frisbee_move_substeps(frisbee: &mut Frisbee, field: &FieldConfig, substeps: u32) -> bool {
+    let substeps = substeps.max(1);
+    let dt = 1.0 / substeps as f64;
+    let mut bounced = false;
+    for _ in 0..substeps {
+        bounced |= frisbee_move(frisbee, field, dt);
+    }
+    bounced
+}
+
+// Outside of the goal the back line is a wall, returns true if the frisbee bounced off it
+pub fn frisbee_back_wall_bounce(frisbee: &mut Frisbee, field: &FieldConfig) -> bool {
+    let wall_ext = field.goal_line();
+    if (frisbee.pos.x >= wall_ext || frisbee.pos.x <= -wall_ext) && !field.in_goal(frisbee.pos.y) {
+        frisbee.direction.x = -frisbee.direction.x;
+        frisbee.pos.x = if frisbee.pos.x > 0.0 { wall_ext - 0.001 } else { -wall_ext + 0.001 };
+        return true;
+    }
+    false
 }
 
-pub fn frisbee_collision_goal(frisbee: &mut Frisbee, players: &mut (Player, Player)) -> bool {
-    const WALL_EXT: f64 = 9.4 + 0.5;
-    const FIVE_POINTS_START: f64 = 3.3 / 2.0;
-    const FIVE_POINTS_END: f64 = -3.3 / 2.0;
+pub fn frisbee_collision_goal(frisbee: &mut Frisbee, players: &mut (Player, Player), zones: &[ScoringZone], field: &FieldConfig) -> bool {
+    let wall_ext = field.goal_line();
 
-    if frisbee.pos.x >= WALL_EXT || frisbee.pos.x <= -WALL_EXT {
-        let mut points = 3;
-        if frisbee.pos.y > FIVE_POINTS_END && frisbee.pos.y < FIVE_POINTS_START {
-            points = 5;
+    if frisbee.pos.x >= wall_ext || frisbee.pos.x <= -wall_ext {
+        if frisbee_back_wall_bounce(frisbee, field) {
+            return false;
         }
 
+        let points = ::game_engine::zone_points(zones, frisbee.pos.y);
+
         match frisbee.last_held {
             Some(PlayerSide::Left) => players.0.score += points,
             Some(PlayerSide::Right) => players.1.score += points,
             None => return false,
         }
-        frisbee.pos = Vector2::new(0.0, -4.0);
+        frisbee.pos = field.frisbee_spawn();
         frisbee.speed = 0.0;
         frisbee.direction = Vector2::zero();
         return true;
@@ -94,25 +177,14 @@ pub fn frisbee_collision_goal(frisbee: &mut Frisbee, players: &mut (Player, Play
     false
 }
 
-pub fn player_collides_with_frisbee(player: &Player, frisbee: &Frisbee) -> bool {
-    match frisbee.last_held {
-        Some(last_held) => {
-            if last_held == player.side.unwrap() {
-                return false;
-            }
-        },
-        None => {}
-    };
-
-    let player_bounds = Circle {
-        center: player.pos,
-        radius: 0.5
-    };
-    let frisbee_bounds = Circle {
-        center: frisbee.pos,
-        radius: 0.5
-    };
-
-    let d2 = (player_bounds.center.x - frisbee_bounds.center.x).powf(2.0) + (player_bounds.center.y - frisbee_bounds.center.y).powf(2.0);
-    d2 <= (player_bounds.radius + frisbee_bounds.radius).powf(2.0)
+// `catch_radius` is the largest distance between the centers of the player and the frisbee
+pub fn player_collides_with_frisbee(player: &Player, frisbee: &Frisbee, catch_radius: f64) -> bool {
+    if let Some(last_held) = frisbee.last_held {
+        if last_held == player.side.unwrap() {
+            return false;
+        }
+    }
+
+    let d2 = (player.pos.x - frisbee.pos.x).powf(2.0) + (player.pos.y - frisbee.pos.y).powf(2.0);
+    d2 <= catch_radius.powf(2.0)
 }
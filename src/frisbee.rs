@@ -1,28 +1,117 @@
 use vector2::Vector2;
 use player::PlayerSide;
+use game_engine::INITIAL_FRISBEE_SPEED;
 
 use rand::Rng;
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Frisbee {
     pub pos:            Vector2,
     pub direction:      Vector2,
     pub speed:          f64,
     pub held_by_player: Option<PlayerSide>,
     pub last_held:      Option<PlayerSide>,
+    pub curve:          f64, // Spin of the current throw, see `apply_curve`
+    pub config:         FrisbeeConfig,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrisbeeConfig {
+    pub drag:      f64, // Speed lost on each step in flight
+    pub max_speed: f64, // Throws and serves are never faster
+}
+
+impl Default for FrisbeeConfig {
+    fn default() -> Self {
+        Self {
+            drag:      0.0,
+            max_speed: INITIAL_FRISBEE_SPEED,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ThrowDirection {
-    Up = 0,
+    Up,
     LightUp,
     Middle,
     LightDown,
     Down,
+    Custom(Vector2), // Launch vector, normalized when thrown
+    Curved { base: Vector2, curve: f64 }, // Launch vector bending left of its course for a positive curve
+}
+
+impl ThrowDirection {
+    pub fn curve(&self) -> f64 {
+        match self {
+            ThrowDirection::Curved { curve, .. } => *curve,
+            _ => 0.0,
+        }
+    }
+}
+
+// Launch of a throw, `vertical_bias` is added to the vertical part of the throw vector before normalizing it
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThrowPower {
+    pub speed:         f64, // Still capped by `FrisbeeConfig::max_speed`
+    pub vertical_bias: f64,
+}
+
+impl Default for ThrowPower {
+    fn default() -> Self {
+        Self {
+            speed:         INITIAL_FRISBEE_SPEED,
+            vertical_bias: 0.0,
+        }
+    }
+}
+
+// Launch of each throw direction, for balancing
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThrowParams {
+    pub straight: [ThrowPower; 5], // In the order of `THROW_DIRECTIONS`
+    pub aimed:    ThrowPower, // Custom and curved throws
+}
+
+impl ThrowParams {
+    pub fn power(&self, dir: &ThrowDirection) -> ThrowPower {
+        match dir {
+            ThrowDirection::Up => self.straight[0],
+            ThrowDirection::LightUp => self.straight[1],
+            ThrowDirection::Middle => self.straight[2],
+            ThrowDirection::LightDown => self.straight[3],
+            ThrowDirection::Down => self.straight[4],
+            ThrowDirection::Custom(_) | ThrowDirection::Curved { .. } => self.aimed,
+        }
+    }
+
+    pub fn set_power(&mut self, dir: &ThrowDirection, power: ThrowPower) {
+        match dir {
+            ThrowDirection::Up => self.straight[0] = power,
+            ThrowDirection::LightUp => self.straight[1] = power,
+            ThrowDirection::Middle => self.straight[2] = power,
+            ThrowDirection::LightDown => self.straight[3] = power,
+            ThrowDirection::Down => self.straight[4] = power,
+            ThrowDirection::Custom(_) | ThrowDirection::Curved { .. } => self.aimed = power,
+        };
+    }
 }
 
-pub fn random_throw_direction() -> ThrowDirection {
-    let mut rng = ::rand::thread_rng();
+// The straight throws, what the search agents try by default
+pub const THROW_DIRECTIONS: [ThrowDirection; 5] = [
+    ThrowDirection::Up,
+    ThrowDirection::LightUp,
+    ThrowDirection::Middle,
+    ThrowDirection::LightDown,
+    ThrowDirection::Down,
+];
+
+pub fn random_throw_direction<R: Rng>(rng: &mut R) -> ThrowDirection {
     match rng.gen_range(0, 5) {
         0 => ThrowDirection::Up,
         1 => ThrowDirection::LightUp,
@@ -32,6 +121,25 @@ pub fn random_throw_direction() -> ThrowDirection {
     }
 }
 
+// Samples `THROW_DIRECTIONS` proportionally to `weights`, uniformly if they are all zero
+pub fn random_throw_direction_weighted<R: Rng>(weights: &[f32; 5], rng: &mut R) -> ThrowDirection {
+    let total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+    if total <= 0.0 {
+        return random_throw_direction(rng);
+    }
+    let mut rand = rng.gen_range(0.0, total);
+    for (dir, weight) in THROW_DIRECTIONS.iter().zip(weights.iter()) {
+        let weight = weight.max(0.0);
+        if rand < weight {
+            return *dir;
+        }
+        rand -= weight;
+    }
+    // Rounding can leave `rand` just past the last weight
+    let last = weights.iter().rposition(|&w| w > 0.0).unwrap();
+    THROW_DIRECTIONS[last]
+}
+
 impl Frisbee {
     pub fn new() -> Self {
         Self {
@@ -39,7 +147,36 @@ impl Frisbee {
             direction:      Vector2::zero(),
             speed:          0.0,
             held_by_player: None,
-            last_held:      None
+            last_held:      None,
+            curve:          0.0,
+            config:         FrisbeeConfig::default(),
+        }
+    }
+
+    // Sends the frisbee flying at `speed`, clamped to the maximum speed
+    pub fn launch(&mut self, direction: Vector2, speed: f64) {
+        self.direction = direction;
+        self.speed = speed.min(self.config.max_speed);
+    }
+
+    // `dt` is the fraction of a step elapsed, 1 for a whole step
+    pub fn apply_drag(&mut self, dt: f64) {
+        self.speed = (self.speed - self.config.drag * dt).max(0.0);
+    }
+
+    // A curved throw accelerates sideways, turning its direction by about `curve * 0.01` radians per step
+    pub fn apply_curve(&mut self, dt: f64) {
+        if self.curve != 0.0 {
+            let lateral = Vector2::new(-self.direction.y, self.direction.x);
+            self.direction = (self.direction + lateral * self.curve * 0.01 * dt).normalized();
+        }
+    }
+
+    // Distance travelled in one step, zero while a player holds the frisbee
+    pub fn velocity(&self) -> Vector2 {
+        if self.held_by_player.is_some() {
+            return Vector2::zero();
         }
+        self.direction * self.speed * 0.1
     }
 }
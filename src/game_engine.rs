@@ -1,55 +1,350 @@
 use vector2::Vector2;
-use frisbee::Frisbee;
+use frisbee::{ Frisbee, ThrowDirection, THROW_DIRECTIONS };
+pub use frisbee::{ FrisbeeConfig, ThrowParams, ThrowPower };
 use shared_data::SharedData;
-use player::{ Player, PlayerSide };
-use agent::{ Intent, AgentType, Agent, RandomAgent, HumanPlayerAgent, RandomRolloutAgent, DijkstraAgent, TabularQLearningAgent, QValues, HumanIntent, ActionResult };
+use sim::MetricsLogger;
+use player::{ Player, PlayerSide, PlayerConfig };
+use agent::{ Intent, AgentType, AgentParams, Agent, RandomAgent, QValues, QVALUES_ACTIONS, QVALUES_MAX_STATES, HumanIntent, ActionResult };
+pub use agent::AnalogThrowConfig;
 
-use rand::Rng;
+use rand::{ Rng, SeedableRng };
+use rand::rngs::SmallRng;
+use std::collections::VecDeque;
 
 pub const MAX_ROUND_POINTS: i8       = 30;
 pub const MAX_ROUND_TIME: f64        = 60.0;
 pub const INITIAL_THROW_TIME: f64    = 2.0;
 pub const INITIAL_FRISBEE_SPEED: f64 = 2.5;
 pub const PLAYER_DASH_POWER: f64     = 2.5;
+pub const CATCH_RADIUS: f64          = 1.0; // A player and the frisbee are both 0.5 wide
+pub const CURVED_THROW_CURVE: f64    = 0.25;
+pub const OBSERVATION_SIZE: usize    = 13;
+pub const FIVE_POINTS_ZONE_HEIGHT: f64 = 3.3;
 
+pub type AgentSlot = Option<Box<dyn Agent>>;
+// Reward of a side in the state reached by a step
+pub type RewardFn = Box<dyn Fn(&GameEngine, PlayerSide) -> f32 + Send>;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GameEngine {
     pub players:       (Player, Player),
-    pub agents:        (Option<Box<Agent>>, Option<Box<Agent>>),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub agents:        (AgentSlot, AgentSlot),
     pub frisbee:       Frisbee,
     pub time:          f64,
     pub start_time:    f64,
     pub state_of_game: StateOfGame,
+    pub paused_from:   Option<StateOfGame>, // Restored when the match is resumed
+    pub scoring_zones: Vec<ScoringZone>,
+    pub field:         FieldConfig,
+    pub catch_radius:  f64, // A free frisbee is caught by a player closer than this
+    pub manual_catch:  bool, // Only `Intent::Grab` catches the frisbee, being close enough isn't enough
+    pub block_mode:    BlockMode,
+    pub player_collision: bool, // Players are kept apart by the net and pushed out of each other, when off, they can meet and overlap at the middle line
+    pub substeps:      u32, // The frisbee flight of each step is integrated in this many moves with catches checked between them, agents still act once per step
+    pub throw_params:  ThrowParams,
+    pub frame:         u64, // Frames stepped since the last reset
+    pub max_frames:    Option<u64>, // The match is a draw once it lasted this long
+    pub stalemate:     Option<StalemateConfig>, // The match is a draw once a state repeats too often
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub hash_history:  VecDeque<u64>, // Hashes of the last frames in play, most recent last
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub events:        Vec<GameEvent>, // Accumulated until `take_events`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub intent_history: (VecDeque<Intent>, VecDeque<Intent>), // Intents applied by each side, most recent last
+    pub intent_history_capacity: usize, // Intents are only kept when not 0
+    #[cfg_attr(feature = "serde", serde(skip, default = "new_engine_rng"))]
+    pub rng:           SmallRng, // Not copied by `copy_in`
+    #[cfg_attr(feature = "serde", serde(skip, default = "new_side_rngs"))]
+    pub side_rngs:     (SmallRng, SmallRng), // Agents' draws, see `rng_for`, not copied by `copy_in` either
 
     // Agent-specific fields
     pub inputs:        (HumanIntent, HumanIntent), // Human agent / Q-Learning
+    pub human_inputs:  (Option<HumanIntent>, Option<HumanIntent>), // Human agent, pushed by a frontend for the next epoch only
+    pub inputs_analog: (Vector2, Vector2), // Human agent, stick directions used over the bitflags when not zero
+    pub analog_throws: AnalogThrowConfig, // Human agent, see `agent::human_intent_to_intent_analog`
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub q_values:      QValues, // Q-Learning
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub q_values_b:    QValues, // Double Q-Learning, `q_values` is the first table
     pub rewards:       (f32, f32), // Q-Learning
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub reward_fn:     Option<RewardFn>, // Q-Learning, replaces the default rewards of every step when set, not copied by `copy_in`
     pub q_scored:      bool, // Q-Learning
     pub explo_rate:    f32, // Q-Learning
+    pub exploration:   ExplorationStrategy, // Q-Learning
+    pub eval_mode:     bool, // Q-Learning / SARSA, the learners play greedily and leave their tables untouched
+    pub learning_rate:   f32, // Q-Learning / SARSA
+    pub discount_factor: f32, // Q-Learning / SARSA
+    pub epsilon_schedule: Option<EpsilonSchedule>, // Q-Learning / SARSA
+    pub episode:         u32, // Resets and episode resets since the engine was created
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub experience:      VecDeque<Transition>, // Q-Learning, the most recent transitions first in, first out
+    pub experience_capacity: usize, // Q-Learning, transitions are only kept when not 0
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub metrics:         Option<MetricsLogger>, // Q-Learning, records every episode ended by `reset_episode` when set, not copied by `copy_in`
+}
+
+// Two agents can get stuck repeating the same moves, the match is called a draw when
+// a state comes back more than `repeats` times in the last `window` frames in play
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StalemateConfig {
+    pub window:  usize,
+    pub repeats: usize,
+}
+
+// A transition given to `update_q_value`, kept to be learned from again by `replay_sample_and_update`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Transition {
+    pub side:       PlayerSide,
+    pub state:      u64,
+    pub action:     usize,
+    pub reward:     f32,
+    pub next_state: u64,
+}
+
+// Outcome of `step_rl` for external learners
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct StepResult {
+    pub reward_left:  f32,
+    pub reward_right: f32,
+    pub done:         bool, // The step ended the round or the match
+}
+
+// What a free frisbee does when it reaches a player who doesn't catch it
+// `Intent::Grab` always catches it, `Catch` also catches it on contact unless `manual_catch` is set
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BlockMode {
+    Catch,
+    Bounce, // Sent back the way the player faces, the blocker becomes its last holder
+    PassThrough,
+}
+
+// How the Q-learner explores, `EpsilonGreedy` uses `explo_rate` and `Boltzmann` the agent's temperature
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExplorationStrategy {
+    EpsilonGreedy,
+    Boltzmann, // Actions are picked with a probability proportional to exp(Q / temperature)
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EpsilonDecay {
+    Linear,
+    Exponential,
+}
+
+// Anneals `explo_rate` from `start` to `end` over `decay_episodes` resets
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EpsilonSchedule {
+    pub start:          f32,
+    pub end:            f32,
+    pub decay_episodes: u32,
+    pub decay:          EpsilonDecay,
+    pub first_episode:  u32, // Engine episode the schedule was set on, `epsilon` counts from it
+}
+
+impl EpsilonSchedule {
+    pub fn epsilon(&self, episode: u32) -> f32 {
+        if self.decay_episodes == 0 || episode >= self.decay_episodes {
+            return self.end;
+        }
+        let progress = episode as f32 / self.decay_episodes as f32;
+        let weight = match self.decay {
+            EpsilonDecay::Linear => 1.0 - progress,
+            EpsilonDecay::Exponential => {
+                // Goes from 1 to 0, dropping quickly at first
+                const FLOOR: f32 = 0.01;
+                (FLOOR.powf(progress) - FLOOR) / (1.0 - FLOOR)
+            },
+        };
+        self.end + (self.start - self.end) * weight
+    }
+}
+
+// Something that happened to the frisbee during a step
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    Caught(PlayerSide),
+    Thrown(PlayerSide, ThrowDirection),
+    WallBounce,
+    Scored(PlayerSide),
+}
+
+// Dimensions of the arena, centered on the net
+// The goal spans `goal_height` around the middle of each back line, the rest of the line is a wall
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldConfig {
+    pub width:       f64, // From one goal line to the other
+    pub height:      f64, // From one side wall to the other
+    pub goal_height: f64,
+}
+
+impl FieldConfig {
+    pub fn new(width: f64, height: f64, goal_height: f64) -> Self {
+        Self {
+            width,
+            height,
+            goal_height,
+        }
+    }
+
+    pub fn goal_line(&self) -> f64 {
+        self.width / 2.0
+    }
+
+    // Players stay half a unit away from the goal line
+    pub fn back_wall(&self) -> f64 {
+        self.width / 2.0 - 0.5
+    }
+
+    pub fn side_wall(&self) -> f64 {
+        self.height / 2.0
+    }
+
+    // Where the players start a round, a little ahead of their goal
+    pub fn spawn(&self, side: PlayerSide) -> Vector2 {
+        let x = self.goal_line() - 0.9;
+        match side {
+            PlayerSide::Left => Vector2::new(-x, 0.0),
+            PlayerSide::Right => Vector2::new(x, 0.0),
+        }
+    }
+
+    // Where the frisbee waits for the serve, close to the bottom wall
+    pub fn frisbee_spawn(&self) -> Vector2 {
+        Vector2::new(0.0, -(self.side_wall() - 0.4).max(0.0))
+    }
+
+    pub fn in_goal(&self, y: f64) -> bool {
+        // The frisbee can overshoot the side walls by a step, a full height goal must still catch it
+        self.goal_height >= self.height || y.abs() <= self.goal_height / 2.0
+    }
+}
+
+impl Default for FieldConfig {
+    fn default() -> Self {
+        Self::new(19.8, 8.8, 8.8)
+    }
+}
+
+// A section of the goal line, the frisbee scores `points` when it crosses strictly between `min_y` and `max_y`
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScoringZone {
+    pub min_y:  f64,
+    pub max_y:  f64,
+    pub points: i8,
+}
+
+impl ScoringZone {
+    pub fn new(y_range: (f64, f64), points: i8) -> Self {
+        Self {
+            min_y: y_range.0,
+            max_y: y_range.1,
+            points,
+        }
+    }
+
+    pub fn contains(&self, y: f64) -> bool {
+        y > self.min_y && y < self.max_y
+    }
+}
+
+// Zones are checked in order and the first one containing the frisbee wins:
+// 5 points in the middle of the goal, 3 points everywhere else
+pub fn default_scoring_zones() -> Vec<ScoringZone> {
+    vec![
+        ScoringZone::new((-FIVE_POINTS_ZONE_HEIGHT / 2.0, FIVE_POINTS_ZONE_HEIGHT / 2.0), 5),
+        ScoringZone::new((f64::MIN, f64::MAX), 3),
+    ]
+}
+
+pub fn zone_points(zones: &[ScoringZone], y: f64) -> i8 {
+    match zones.iter().find(|zone| zone.contains(y)) {
+        Some(zone) => zone.points,
+        None => 0,
+    }
 }
 
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum StateOfGame {
     Start,
     Playing,
-    End,
+    Won(PlayerSide),
+    Draw, // Stopped by `max_frames`, a stalemate or a tie when the time runs out
+    Paused, // Frozen by `set_paused`, the state to resume is kept in `GameEngine::paused_from`
 }
 
+// The C interface only knows the match ended, the winner is told by the scores
 pub fn state_to_i8(state: &StateOfGame) -> i8 {
-    *state as i8
+    match state {
+        StateOfGame::Start => 0,
+        StateOfGame::Playing => 1,
+        StateOfGame::Won(_) => 2,
+        StateOfGame::Draw => 3,
+        StateOfGame::Paused => 4,
+    }
 }
 
-pub fn state_from_i8(state: i8) -> StateOfGame {
+pub fn state_from_i8(state: i8, scores: (i8, i8)) -> StateOfGame {
     match state {
         0 => StateOfGame::Start,
         1 => StateOfGame::Playing,
-        _ => StateOfGame::End,
+        3 => StateOfGame::Draw,
+        4 => StateOfGame::Paused,
+        _ => match winner(scores) {
+            Some(side) => StateOfGame::Won(side),
+            None => StateOfGame::Draw,
+        },
+    }
+}
+
+pub fn winner(scores: (i8, i8)) -> Option<PlayerSide> {
+    if scores.0 > scores.1 {
+        Some(PlayerSide::Left)
+    } else if scores.1 > scores.0 {
+        Some(PlayerSide::Right)
+    } else {
+        None
+    }
+}
+
+fn is_random_agent(agent: &AgentSlot) -> bool {
+    match agent {
+        Some(agent) => agent.get_type() == AgentType::Random,
+        None => false,
+    }
+}
+
+fn new_engine_rng() -> SmallRng {
+    SmallRng::from_rng(::rand::thread_rng()).unwrap()
+}
+
+fn new_side_rngs() -> (SmallRng, SmallRng) {
+    (new_engine_rng(), new_engine_rng())
+}
+
+// Seed of a side's stream, the master seed with the side mixed into its bits
+fn side_seed(seed: u64, side: PlayerSide) -> u64 {
+    seed ^ (side as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+impl Default for GameEngine {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl GameEngine {
     #[no_mangle]
-    pub extern fn initialize() -> *mut Self {
+    pub extern "C" fn initialize() -> *mut Self {
         let boxed = Box::new(Self::new());
         Box::into_raw(boxed)
     }
@@ -60,25 +355,31 @@ impl GameEngine {
 
         let mut file = OpenOptions::new()
             .create(true)
-            .write(true)
             .append(true)
             .open("rustjammers_debug.log")
             .unwrap();
 
         let mut s = String::from(s);
         s.push_str("\r\n");
-        file.write(&s.into_bytes()).unwrap();
+        file.write_all(&s.into_bytes()).unwrap();
         file.flush().unwrap();
     }
 
+    /// # Safety
+    ///
+    /// `ptr` must be null or a pointer returned by `initialize` that has not been disposed yet.
     #[no_mangle]
-    pub unsafe extern fn dispose(ptr: *mut Self) {
+    pub unsafe extern "C" fn dispose(ptr: *mut Self) {
         if !ptr.is_null() {
             let _state: Box<Self> = Box::from_raw(ptr);
         }
     }
 
     pub fn new() -> Self {
+        Self::with_config(FieldConfig::default())
+    }
+
+    pub fn with_config(field: FieldConfig) -> Self {
         Self {
             players: (
                 Player::new(),
@@ -92,59 +393,438 @@ impl GameEngine {
             time: 0.0,
             start_time: 0.0,
             state_of_game: StateOfGame::Start,
+            paused_from: None,
+            scoring_zones: default_scoring_zones(),
+            field,
+            catch_radius: CATCH_RADIUS,
+            manual_catch: false,
+            block_mode: BlockMode::Catch,
+            player_collision: true,
+            substeps: 1,
+            throw_params: ThrowParams::default(),
+            frame: 0,
+            max_frames: None,
+            stalemate: None,
+            hash_history: VecDeque::new(),
+            events: Vec::new(),
+            intent_history: (VecDeque::new(), VecDeque::new()),
+            intent_history_capacity: 0,
+            rng: new_engine_rng(),
+            side_rngs: new_side_rngs(),
 
             inputs: (
                 HumanIntent::IDLE,
                 HumanIntent::IDLE,
             ),
+            human_inputs: (None, None),
+            inputs_analog: (Vector2::zero(), Vector2::zero()),
+            analog_throws: AnalogThrowConfig::default(),
             q_values: QValues::new(),
+            q_values_b: QValues::new(),
             rewards: (0.0, 0.0),
+            reward_fn: None,
             q_scored: false,
             explo_rate: 0.05,
+            exploration: ExplorationStrategy::EpsilonGreedy,
+            eval_mode: false,
+            learning_rate: 0.8,
+            discount_factor: 0.95,
+            epsilon_schedule: None,
+            episode: 0,
+            experience: VecDeque::new(),
+            experience_capacity: 0,
+            metrics: None,
         }
     }
 
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.rng = SmallRng::seed_from_u64(seed);
+        self.side_rngs = (
+            SmallRng::seed_from_u64(side_seed(seed, PlayerSide::Left)),
+            SmallRng::seed_from_u64(side_seed(seed, PlayerSide::Right)),
+        );
+    }
+
+    // The stream agents playing `side` draw from, so one side's randomness doesn't depend on what the other consumes
+    // The engine's own draws (serves, replay sampling, table updates) stay on `rng`
+    pub fn rng_for(&mut self, side: PlayerSide) -> &mut SmallRng {
+        match side {
+            PlayerSide::Left => &mut self.side_rngs.0,
+            PlayerSide::Right => &mut self.side_rngs.1,
+        }
+    }
+
+    // Overwrites the match state of `new_game_engine` in place, so simulations can reuse one scratch engine
+    // Nothing is reallocated: the Q-table, rng and hyperparameters of the target are left untouched,
+    // and its agents are only replaced if they aren't random agents already
     pub fn copy_in(&self, new_game_engine: &mut GameEngine) {
         new_game_engine.players = self.players;
-        new_game_engine.agents = (
-            Some(Box::new(RandomAgent {})),
-            Some(Box::new(RandomAgent {}))
-        );
+        if !is_random_agent(&new_game_engine.agents.0) {
+            new_game_engine.agents.0 = Some(Box::new(RandomAgent::new()));
+        }
+        if !is_random_agent(&new_game_engine.agents.1) {
+            new_game_engine.agents.1 = Some(Box::new(RandomAgent::new()));
+        }
         new_game_engine.frisbee = self.frisbee;
         new_game_engine.inputs = self.inputs;
         new_game_engine.time = self.time;
         new_game_engine.start_time = self.start_time;
         new_game_engine.state_of_game = self.state_of_game;
+        new_game_engine.paused_from = self.paused_from;
+        new_game_engine.scoring_zones.clone_from(&self.scoring_zones);
+        new_game_engine.field = self.field;
+        new_game_engine.catch_radius = self.catch_radius;
+        new_game_engine.manual_catch = self.manual_catch;
+        new_game_engine.block_mode = self.block_mode;
+        new_game_engine.player_collision = self.player_collision;
+        new_game_engine.substeps = self.substeps;
+        new_game_engine.throw_params = self.throw_params;
+        new_game_engine.frame = self.frame;
+        new_game_engine.episode = self.episode;
+        new_game_engine.max_frames = self.max_frames;
+        new_game_engine.stalemate = self.stalemate;
+        new_game_engine.hash_history.clone_from(&self.hash_history);
+        // Simulations step copies a lot, don't let their events pile up
+        new_game_engine.events.clear();
+        new_game_engine.rewards = self.rewards;
+        new_game_engine.q_scored = self.q_scored;
+    }
+
+    // The same match with the players swapped and the field flipped left to right, to double the training data
+    // It starts from `copy_in`, so its agents are random ones; the stalemate history is dropped since its hashes don't mirror
+    pub fn mirror(&self) -> GameEngine {
+        fn mirror_vector(v: Vector2) -> Vector2 {
+            Vector2::new(-v.x, v.y)
+        }
+        fn mirror_player(player: &Player, side: PlayerSide) -> Player {
+            let mut mirrored = *player;
+            mirrored.side = Some(side);
+            mirrored.pos = mirror_vector(player.pos);
+            if let Some(ref mut slide) = mirrored.slide {
+                slide.target = mirror_vector(slide.target);
+                slide.origin = mirror_vector(slide.origin);
+                slide.dir = mirror_vector(slide.dir);
+            }
+            mirrored
+        }
+        fn mirror_side(side: Option<PlayerSide>) -> Option<PlayerSide> {
+            side.map(|side| match side {
+                PlayerSide::Left => PlayerSide::Right,
+                PlayerSide::Right => PlayerSide::Left,
+            })
+        }
+
+        let mut mirrored = GameEngine::new();
+        self.copy_in(&mut mirrored);
+        mirrored.hash_history.clear();
+        mirrored.players = (
+            mirror_player(&self.players.1, PlayerSide::Left),
+            mirror_player(&self.players.0, PlayerSide::Right),
+        );
+        mirrored.frisbee.pos = mirror_vector(self.frisbee.pos);
+        mirrored.frisbee.direction = mirror_vector(self.frisbee.direction);
+        // Flipped, a throw curving left curves right
+        mirrored.frisbee.curve = -self.frisbee.curve;
+        mirrored.frisbee.held_by_player = mirror_side(self.frisbee.held_by_player);
+        mirrored.frisbee.last_held = mirror_side(self.frisbee.last_held);
+        mirrored.inputs = (::agent::mirror_human_intent(self.inputs.1), ::agent::mirror_human_intent(self.inputs.0));
+        mirrored.inputs_analog = (mirror_vector(self.inputs_analog.1), mirror_vector(self.inputs_analog.0));
+        mirrored.rewards = (self.rewards.1, self.rewards.0);
+        mirrored.state_of_game = match self.state_of_game {
+            StateOfGame::Won(side) => StateOfGame::Won(mirror_side(Some(side)).unwrap()),
+            state => state,
+        };
+        mirrored
+    }
+
+    // Asks `agent` what it would do in the current state
+    // It acts on a copy of the engine so agents that write to it (like the Q-learner with `inputs`) leave the live match untouched
+    pub fn ask_agent(&self, agent: &mut dyn Agent, side: PlayerSide) -> Intent {
+        let mut scratch = GameEngine::new();
+        self.copy_in(&mut scratch);
+        scratch.explo_rate = self.explo_rate;
+        scratch.exploration = self.exploration;
+        scratch.eval_mode = self.eval_mode;
+        if ::agent::uses_q_values(agent.get_type()) {
+            // The table is big, only clone it for the agents that read it
+            scratch.q_values = self.q_values.clone();
+            if agent.get_type() == AgentType::DoubleQLearning {
+                scratch.q_values_b = self.q_values_b.clone();
+            }
+        }
+        agent.act(side, &mut scratch)
+    }
+
+    // Every intent `side` can meaningfully choose: the five throws while holding the frisbee,
+    // otherwise the eight moves and eight dashes, or nothing at all while dashing
+    pub fn legal_intents(&self, side: PlayerSide) -> Vec<Intent> {
+        self.legal_intents_with_throws(side, &THROW_DIRECTIONS, false)
+    }
+
+    // Same as `legal_intents`, trying only `throws` while holding the frisbee,
+    // with a throw curving each way after them when `curved_throws` is set
+    pub fn legal_intents_with_throws(&self, side: PlayerSide, throws: &[ThrowDirection], curved_throws: bool) -> Vec<Intent> {
+        let player = match side {
+            PlayerSide::Left => &self.players.0,
+            PlayerSide::Right => &self.players.1,
+        };
+
+        let mut intents = Vec::new();
+        match self.frisbee.held_by_player {
+            Some(held_by) if held_by == side => {
+                for dir in throws.iter() {
+                    intents.push(Intent::Throw(*dir));
+                }
+                if curved_throws {
+                    let base = Vector2::new(player.get_horizontal_aim_direction(), 0.0);
+                    intents.push(Intent::Throw(ThrowDirection::Curved { base, curve: CURVED_THROW_CURVE }));
+                    intents.push(Intent::Throw(ThrowDirection::Curved { base, curve: -CURVED_THROW_CURVE }));
+                }
+            },
+            _ => {
+                if self.requires_grab() {
+                    intents.push(Intent::Grab);
+                }
+                if player.slide.is_none() {
+                    let directions = [
+                        Vector2::new(0.0, 1.0),
+                        Vector2::new(0.0, -1.0),
+                        Vector2::new(-1.0, 0.0),
+                        Vector2::new(1.0, 0.0),
+                        Vector2::new(-1.0, -1.0).normalized(),
+                        Vector2::new(-1.0, 1.0).normalized(),
+                        Vector2::new(1.0, -1.0).normalized(),
+                        Vector2::new(1.0, 1.0).normalized(),
+                    ];
+                    for dir in directions.iter() {
+                        intents.push(Intent::Move(*dir));
+                    }
+                    if player.can_dash() {
+                        for dir in directions.iter() {
+                            intents.push(Intent::Dash(*dir));
+                        }
+                    }
+                }
+            }
+        };
+        intents
+    }
+
+    pub fn set_frisbee_config(&mut self, config: FrisbeeConfig) {
+        self.frisbee.config = config;
+    }
+
+    pub fn set_throw_power(&mut self, dir: ThrowDirection, power: ThrowPower) {
+        self.throw_params.set_power(&dir, power);
+    }
+
+    // Takes effect from the next move or dash, a slide in progress keeps its remaining frames
+    pub fn set_player_config(&mut self, side: PlayerSide, config: PlayerConfig) {
+        match side {
+            PlayerSide::Left => self.players.0.config = config,
+            PlayerSide::Right => self.players.1.config = config,
+        };
+    }
+
+    // How close to the middle line the players may stand
+    fn net(&self) -> f64 {
+        if self.player_collision { ::collision::NET } else { 0.0 }
+    }
+
+    // Places a player for a crafted scenario, kept on its own half of the field like `collision::player_collision` does in play
+    // A player holding the frisbee carries it along
+    pub fn set_player_pos(&mut self, side: PlayerSide, pos: Vector2) {
+        let net = self.net();
+        let player = match side {
+            PlayerSide::Left => &mut self.players.0,
+            PlayerSide::Right => &mut self.players.1,
+        };
+        player.pos = pos;
+        ::collision::player_collision(player, &self.field, net);
+        if self.frisbee.held_by_player == Some(side) {
+            self.frisbee.pos = player.pos;
+        }
+    }
+
+    // Places the frisbee for a crafted scenario, `velocity` is the distance travelled per step as returned by `Frisbee::velocity`
+    // A held frisbee sits in its holder's hands and doesn't move, whatever `pos` and `velocity` say
+    pub fn set_frisbee(&mut self, pos: Vector2, velocity: Vector2, held_by: Option<PlayerSide>) {
+        self.frisbee.held_by_player = held_by;
+        self.frisbee.curve = 0.0;
+        match held_by {
+            Some(side) => {
+                self.frisbee.pos = match side {
+                    PlayerSide::Left => self.players.0.pos,
+                    PlayerSide::Right => self.players.1.pos,
+                };
+                self.frisbee.direction = Vector2::zero();
+                self.frisbee.speed = 0.0;
+            },
+            None => {
+                self.frisbee.pos = pos;
+                self.frisbee.direction = velocity.normalized();
+                self.frisbee.speed = (velocity.length() * 10.0).min(self.frisbee.config.max_speed);
+            },
+        };
+    }
+
+    pub fn set_hyperparameters(&mut self, alpha: f32, gamma: f32) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&alpha) {
+            return Err(format!("Learning rate must be in [0, 1], got {}.", alpha));
+        }
+        if !(0.0..=1.0).contains(&gamma) {
+            return Err(format!("Discount factor must be in [0, 1], got {}.", gamma));
+        }
+        self.learning_rate = alpha;
+        self.discount_factor = gamma;
+        Ok(())
+    }
+
+    pub fn set_epsilon_schedule(&mut self, start: f32, end: f32, decay_episodes: u32) {
+        self.set_epsilon_schedule_with(start, end, decay_episodes, EpsilonDecay::Linear);
+    }
+
+    pub fn set_epsilon_schedule_with(&mut self, start: f32, end: f32, decay_episodes: u32, decay: EpsilonDecay) {
+        let schedule = EpsilonSchedule {
+            start,
+            end,
+            decay_episodes,
+            decay,
+            first_episode: self.episode,
+        };
+        self.explo_rate = schedule.epsilon(0);
+        self.epsilon_schedule = Some(schedule);
+    }
+
+    // Off-policy update: Q(s, a) moves toward r + gamma * max Q(s', .)
+    // The transition is also kept for experience replay if it is enabled
+    pub fn update_q_value(&mut self, side: PlayerSide, state: u64, action: usize, reward: f32, next_state: u64) {
+        if self.experience_capacity > 0 {
+            if self.experience.len() >= self.experience_capacity {
+                self.experience.pop_front();
+            }
+            self.experience.push_back(Transition { side, state, action, reward, next_state });
+        }
+        self.apply_q_update(side, state, action, reward, next_state);
+    }
+
+    // Keeps the last `capacity` intents applied by each side, 0 disables the history
+    pub fn set_intent_history_capacity(&mut self, capacity: usize) {
+        self.intent_history_capacity = capacity;
+        for history in [&mut self.intent_history.0, &mut self.intent_history.1].iter_mut() {
+            while history.len() > capacity {
+                history.pop_front();
+            }
+        }
+    }
+
+    // Oldest first
+    pub fn intent_history(&self, side: PlayerSide) -> &VecDeque<Intent> {
+        match side {
+            PlayerSide::Left => &self.intent_history.0,
+            PlayerSide::Right => &self.intent_history.1,
+        }
+    }
+
+    // Keeps the last `capacity` transitions for experience replay, 0 disables it
+    pub fn set_experience_capacity(&mut self, capacity: usize) {
+        self.experience_capacity = capacity;
+        while self.experience.len() > capacity {
+            self.experience.pop_front();
+        }
     }
 
-    fn create_agent_from_type(agent_type: AgentType, frames: f64, sim: i8) -> Box<Agent> {
-        match agent_type {
-            AgentType::Random =>           Box::new(RandomAgent {}),
-            AgentType::HumanPlayer =>      Box::new(HumanPlayerAgent {}),
-            AgentType::RandomRollout =>    Box::new(RandomRolloutAgent {frames: frames,sim: sim}),
-            AgentType::Dijkstra =>         Box::new(DijkstraAgent {}),
-            AgentType::TabularQLearning => Box::new(TabularQLearningAgent {}),
-            AgentType::None =>             panic!("Invalid agent type."),
+    // Learns again from `batch` transitions drawn at random among the kept ones, returns the number replayed
+    pub fn replay_sample_and_update(&mut self, batch: usize) -> usize {
+        if self.experience.is_empty() {
+            return 0;
+        }
+        for _ in 0..batch {
+            let t = self.experience[self.rng.gen_range(0, self.experience.len())];
+            self.apply_q_update(t.side, t.state, t.action, t.reward, t.next_state);
         }
+        batch
+    }
+
+    fn apply_q_update(&mut self, side: PlayerSide, state: u64, action: usize, reward: f32, next_state: u64) {
+        let next_max = match ::agent::side_q_values(&self.q_values, next_state, side) {
+            Some(values) => values.iter().cloned().fold(f32::MIN, f32::max),
+            None => 0.0,
+        };
+        let values = ::agent::side_q_values_mut(&mut self.q_values, state, side);
+        values[action] += self.learning_rate * (reward + self.discount_factor * next_max - values[action]);
+    }
+
+    // Double Q-learning: a coin flip picks the table to update, the best next action is chosen with that table
+    // and valued with the other one
+    // Returns true if the first table (`q_values`) was updated
+    pub fn update_double_q_value(&mut self, side: PlayerSide, state: u64, action: usize, reward: f32, next_state: u64) -> bool {
+        let update_a = self.rng.gen_range(0.0, 1.0) < 0.5;
+        let (updated, other) = if update_a {
+            (&mut self.q_values, &self.q_values_b)
+        } else {
+            (&mut self.q_values_b, &self.q_values)
+        };
+
+        let next_value = match ::agent::side_q_values(updated, next_state, side) {
+            Some(values) => {
+                let best = ::agent::max_index(values, &mut self.rng);
+                match ::agent::side_q_values(other, next_state, side) {
+                    Some(other_values) => other_values[best],
+                    None => 0.0,
+                }
+            },
+            None => 0.0,
+        };
+        let values = ::agent::side_q_values_mut(updated, state, side);
+        values[action] += self.learning_rate * (reward + self.discount_factor * next_value - values[action]);
+        update_a
+    }
+
+    // Q-value of `action_index` for `side` in the current state, states never visited are all zeros
+    pub fn q_value_for(&self, side: PlayerSide, action_index: u8) -> f32 {
+        match ::agent::side_q_values(&self.q_values, self.hash(), side) {
+            Some(values) => values[action_index as usize],
+            None => 0.0,
+        }
+    }
+
+    // The lowest of the best actions, unlike the learners this doesn't need the rng to break ties
+    pub fn greedy_action(&self, side: PlayerSide) -> u8 {
+        match ::agent::side_q_values(&self.q_values, self.hash(), side) {
+            Some(values) => ::agent::max_indices(values)[0] as u8,
+            None => 0,
+        }
+    }
+
+    // Number of states with at least one learned value, for either side
+    pub fn q_table_nonzero_count(&self) -> usize {
+        self.q_values.values()
+            .filter(|(left, right)| left.iter().chain(right.iter()).any(|&v| v != 0.0))
+            .count()
     }
 
     #[no_mangle]
-    pub extern fn reset(&mut self) {
-        self.players.0.pos.x = -9.0;
-        self.players.0.pos.y = 0.0;
+    pub extern "C" fn reset(&mut self) {
+        self.end_agent_episodes();
+
+        self.players.0.pos = self.field.spawn(PlayerSide::Left);
         self.players.0.score = 0;
         self.players.0.side = Some(PlayerSide::Left);
+        self.players.0.slide = None;
+        self.players.0.dash_cooldown = 0;
 
-        self.players.1.pos.x = 9.0;
-        self.players.1.pos.y = 0.0;
+        self.players.1.pos = self.field.spawn(PlayerSide::Right);
         self.players.1.score = 0;
         self.players.1.side = Some(PlayerSide::Right);
+        self.players.1.slide = None;
+        self.players.1.dash_cooldown = 0;
 
-        self.frisbee.pos.x = 0.0;
-        self.frisbee.pos.y = -4.0;
+        self.frisbee.pos = self.field.frisbee_spawn();
         self.frisbee.direction.x = 0.0;
         self.frisbee.direction.y = 0.0;
         self.frisbee.speed = 0.0;
+        self.frisbee.curve = 0.0;
         self.frisbee.last_held = None;
         self.frisbee.held_by_player = None;
 
@@ -152,47 +832,240 @@ impl GameEngine {
         self.start_time = 0.0;
 
         self.state_of_game = StateOfGame::Start;
+        self.paused_from = None;
+        self.frame = 0;
+        self.episode += 1;
+        self.hash_history.clear();
+        self.events.clear();
+        self.intent_history.0.clear();
+        self.intent_history.1.clear();
 
         self.inputs = (HumanIntent::IDLE, HumanIntent::IDLE);
+        self.human_inputs = (None, None);
+        self.inputs_analog = (Vector2::zero(), Vector2::zero());
         self.rewards = (0.0, 0.0);
         self.q_scored = false;
+
+        self.apply_epsilon_schedule();
+    }
+
+    // Sets the exploration rate of the current episode when a schedule is set
+    fn apply_epsilon_schedule(&mut self) {
+        if let Some(schedule) = self.epsilon_schedule {
+            self.explo_rate = schedule.epsilon(self.episode.saturating_sub(schedule.first_episode));
+        }
+    }
+
+    // Restarts the match straight into play, with the frisbee lying free at the center
+    // The Q-tables and hyperparameters are left untouched, a new engine would lose all of them
+    // The exploration rate only changes when an epsilon schedule is set, which moves on to the next episode like `reset` does
+    pub fn reset_episode(&mut self) {
+        if let Some(mut metrics) = self.metrics.take() {
+            metrics.log_episode(self);
+            self.metrics = Some(metrics);
+        }
+        self.end_agent_episodes();
+
+        self.players.0.pos = self.field.spawn(PlayerSide::Left);
+        self.players.0.score = 0;
+        self.players.0.slide = None;
+        self.players.0.dash_cooldown = 0;
+        self.players.1.pos = self.field.spawn(PlayerSide::Right);
+        self.players.1.score = 0;
+        self.players.1.slide = None;
+        self.players.1.dash_cooldown = 0;
+
+        self.frisbee.pos = Vector2::zero();
+        self.frisbee.direction = Vector2::zero();
+        self.frisbee.speed = 0.0;
+        self.frisbee.curve = 0.0;
+        self.frisbee.last_held = None;
+        self.frisbee.held_by_player = None;
+
+        self.time = MAX_ROUND_TIME;
+        self.start_time = 0.0;
+        self.frame = 0;
+        self.episode += 1;
+        self.hash_history.clear();
+        self.intent_history.0.clear();
+        self.intent_history.1.clear();
+        self.state_of_game = StateOfGame::Playing;
+        self.paused_from = None;
+
+        self.inputs = (HumanIntent::IDLE, HumanIntent::IDLE);
+        self.human_inputs = (None, None);
+        self.inputs_analog = (Vector2::zero(), Vector2::zero());
+        self.events.clear();
+        self.rewards = (0.0, 0.0);
+        self.q_scored = false;
+        self.apply_epsilon_schedule();
+    }
+
+    // Lets the agents learn from the last frame while its rewards are still set
+    fn end_agent_episodes(&mut self) {
+        if let Some(mut agent) = self.agents.0.take() {
+            agent.end_episode(PlayerSide::Left, self);
+            self.agents.0 = Some(agent);
+        }
+        if let Some(mut agent) = self.agents.1.take() {
+            agent.end_episode(PlayerSide::Right, self);
+            self.agents.1 = Some(agent);
+        }
     }
 
     #[no_mangle]
-    pub extern fn send_type_p1(&mut self, agent_type: i8, frames: f64, sim: i8) {
+    pub extern "C" fn send_type_p1(&mut self, agent_type: i8, frames: f64, sim: i8) {
         let t = ::agent::agent_type_from_i8(agent_type);
-        self.agents.0 = Some(Self::create_agent_from_type(t, frames, sim));
-        if t == AgentType::TabularQLearning {
-            self.load_q_values();
-        }
+        self.agents.0 = Some(::agent::make_agent_with(t, AgentParams { frames, sim, ..AgentParams::default() }));
+        self.prepare_q_tables(t);
     }
 
     #[no_mangle]
-    pub extern fn send_type_p2(&mut self, agent_type: i8, frames: f64, sim: i8) {
+    pub extern "C" fn send_type_p2(&mut self, agent_type: i8, frames: f64, sim: i8) {
         let t = ::agent::agent_type_from_i8(agent_type);
-        self.agents.1 = Some(Self::create_agent_from_type(t, frames, sim));
-        if t == AgentType::TabularQLearning {
-            self.load_q_values();
+        self.agents.1 = Some(::agent::make_agent_with(t, AgentParams { frames, sim, ..AgentParams::default() }));
+        self.prepare_q_tables(t);
+    }
+
+    fn prepare_q_tables(&mut self, agent_type: AgentType) {
+        if ::agent::uses_q_values(agent_type) {
+            self.load_default_q_values();
+        }
+        if agent_type == AgentType::DoubleQLearning && self.q_values_b.is_empty() {
+            self.q_values_b = ::agent::get_blank_q_values();
         }
     }
 
-    fn load_q_values(&mut self) {
+    fn load_default_q_values(&mut self) {
         if !self.q_values.is_empty() {
             return;
         }
+        // Agents play fine with an empty table, a missing or invalid file is not an error here
+        if self.load_q_values("q_values.bin").is_err() {
+            self.q_values.clear();
+        }
+    }
+
+    // Binary layout, little endian:
+    // number of states (u64), number of actions per side (u64),
+    // then for each state its hash (u64) followed by the left and right action values (f32)
+    // Tables saved with bincode by older versions can't be read, regenerate them with `generate_qvalues`
+    pub fn save_q_values(&self, path: &str) -> ::std::io::Result<()> {
         use ::std::fs::File;
-        use ::std::io::BufReader;
+        use ::std::io::{ BufWriter, Write };
+
+        let mut bw = BufWriter::new(File::create(path)?);
+        bw.write_all(&(self.q_values.len() as u64).to_le_bytes())?;
+        bw.write_all(&(QVALUES_ACTIONS as u64).to_le_bytes())?;
+        for (hash, values) in self.q_values.iter() {
+            bw.write_all(&hash.to_le_bytes())?;
+            for value in values.0.iter().chain(values.1.iter()) {
+                bw.write_all(&value.to_le_bytes())?;
+            }
+        }
+        bw.flush()
+    }
 
-        let f = match File::open("q_values.bin") {
-            Ok(f) => f,
-            _ => return,
-        };
-        let br = BufReader::new(f);
-        self.q_values = ::bincode::deserialize_from(br).unwrap();
+    // Readable dump for inspecting or diffing tables, states sorted by hash: `{ "hash": { "left": [...], "right": [...] } }`
+    // `only_nonzero` skips the states with nothing learned for either side
+    pub fn dump_q_values_json(&self, path: &str, only_nonzero: bool) -> ::std::io::Result<()> {
+        use ::std::fs::File;
+        use ::std::io::{ BufWriter, Write };
+
+        fn write_values(w: &mut dyn Write, values: &[f32; QVALUES_ACTIONS]) -> ::std::io::Result<()> {
+            write!(w, "[")?;
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    write!(w, ", ")?;
+                }
+                // JSON has no NaN nor infinities
+                if value.is_finite() {
+                    write!(w, "{}", value)?;
+                } else {
+                    write!(w, "null")?;
+                }
+            }
+            write!(w, "]")
+        }
+
+        let mut states: Vec<_> = self.q_values.iter()
+            .filter(|(_, (left, right))| !only_nonzero || left.iter().chain(right.iter()).any(|&v| v != 0.0))
+            .collect();
+        states.sort_by_key(|&(hash, _)| *hash);
+
+        let mut bw = BufWriter::new(File::create(path)?);
+        write!(bw, "{{")?;
+        for (i, (hash, (left, right))) in states.iter().enumerate() {
+            write!(bw, "{}\n  \"{}\": {{ \"left\": ", if i > 0 { "," } else { "" }, hash)?;
+            write_values(&mut bw, left)?;
+            write!(bw, ", \"right\": ")?;
+            write_values(&mut bw, right)?;
+            write!(bw, " }}")?;
+        }
+        writeln!(bw, "\n}}")?;
+        bw.flush()
+    }
+
+    pub fn load_q_values(&mut self, path: &str) -> ::std::io::Result<()> {
+        use ::std::fs::File;
+        use ::std::io::{ BufReader, Read, Error, ErrorKind };
+
+        fn read_u64(br: &mut dyn Read) -> ::std::io::Result<u64> {
+            let mut buf = [0u8; 8];
+            br.read_exact(&mut buf)?;
+            Ok(u64::from_le_bytes(buf))
+        }
+
+        fn read_f32(br: &mut dyn Read) -> ::std::io::Result<f32> {
+            let mut buf = [0u8; 4];
+            br.read_exact(&mut buf)?;
+            Ok(f32::from_le_bytes(buf))
+        }
+
+        let mut br = BufReader::new(File::open(path)?);
+        let len = read_u64(&mut br)?;
+        let actions = read_u64(&mut br)?;
+        if actions != QVALUES_ACTIONS as u64 {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Expected {} actions per state, found {}.", QVALUES_ACTIONS, actions)));
+        }
+
+        // The count comes from the file, a corrupt one must fail on reading rather than on allocating
+        let mut q_values = QValues::with_capacity(len.min(QVALUES_MAX_STATES) as usize);
+        for _ in 0..len {
+            let hash = read_u64(&mut br)?;
+            let mut values = ([0.0; QVALUES_ACTIONS], [0.0; QVALUES_ACTIONS]);
+            for value in values.0.iter_mut().chain(values.1.iter_mut()) {
+                *value = read_f32(&mut br)?;
+            }
+            q_values.insert(hash, values);
+        }
+
+        self.q_values = q_values;
+        Ok(())
     }
 
     #[no_mangle]
-    pub extern fn epoch(&mut self, p1_h_action: HumanIntent, p2_h_action: HumanIntent) {
+    pub extern "C" fn epoch(&mut self, p1_h_action: HumanIntent, p2_h_action: HumanIntent) {
+        // Agents aren't even asked while paused, the pending human inputs wait for the match to resume
+        if self.state_of_game == StateOfGame::Paused {
+            return;
+        }
+        let intents = self.select_intents(p1_h_action, p2_h_action);
+        self.step(intents);
+    }
+
+    // Input of the human player on `side` for the next epoch, it replaces the one passed to `epoch` and is then cleared
+    pub fn set_human_input(&mut self, side: PlayerSide, input: HumanIntent) {
+        match side {
+            PlayerSide::Left => self.human_inputs.0 = Some(input),
+            PlayerSide::Right => self.human_inputs.1 = Some(input),
+        };
+    }
+
+    // Asks both agents for their intents for the next frame without stepping the engine
+    pub fn select_intents(&mut self, p1_h_action: HumanIntent, p2_h_action: HumanIntent) -> (Intent, Intent) {
+        let p1_h_action = self.human_inputs.0.take().unwrap_or(p1_h_action);
+        let p2_h_action = self.human_inputs.1.take().unwrap_or(p2_h_action);
         let mut a1 = self.agents.0.take().unwrap();
         let mut a2 = self.agents.1.take().unwrap();
 
@@ -215,20 +1088,152 @@ impl GameEngine {
             Some(a2)
         );
 
-        self.step((
-            action_p1,
-            action_p2
-        ));
+        (action_p1, action_p2)
+    }
+
+    // Same as `select_intents` for `side` alone, the other agent isn't asked
+    pub fn select_intent(&mut self, side: PlayerSide, h_action: HumanIntent) -> Intent {
+        let (mut agent, h_action) = match side {
+            PlayerSide::Left => (self.agents.0.take().unwrap(), self.human_inputs.0.take().unwrap_or(h_action)),
+            PlayerSide::Right => (self.agents.1.take().unwrap(), self.human_inputs.1.take().unwrap_or(h_action)),
+        };
+        let input = match agent.get_type() {
+            AgentType::HumanPlayer => h_action,
+            _ => HumanIntent::IDLE
+        };
+        match side {
+            PlayerSide::Left => self.inputs.0 = input,
+            PlayerSide::Right => self.inputs.1 = input,
+        };
+        let intent = agent.act(side, self);
+        match side {
+            PlayerSide::Left => self.agents.0 = Some(agent),
+            PlayerSide::Right => self.agents.1 = Some(agent),
+        };
+        intent
     }
 
     #[no_mangle]
-    pub extern fn get_state(&mut self) -> SharedData {
+    pub extern "C" fn get_state(&mut self) -> SharedData {
         let mut data = SharedData::new();
         self.to_shared_data(&mut data);
         data
     }
 
+    // Fixed-length state for external learners, as seen by `side` ("own" is `side`, "other" its opponent)
+    // Every component is clamped to [-1, 1]:
+    //  0, 1: own position, x over the goal line and y over the side wall
+    //  2, 3: other position, same scale
+    //  4, 5: frisbee position, same scale
+    //  6, 7: frisbee velocity, 1 is the speed of a throw
+    //  8:    possession, 1 if own holds the frisbee, -1 if other does, 0 if nobody does
+    //  9:    own dash cooldown, the fraction of `dash_cooldown_frames` left
+    //  10:   other dash cooldown, same scale
+    //  11:   own dash frames remaining, the fraction of `dash_duration_frames` left
+    //  12:   other dash frames remaining, same scale
+    pub fn observation(&self, side: PlayerSide) -> [f32; OBSERVATION_SIZE] {
+        fn cooldown(player: &Player) -> f64 {
+            match player.config.dash_cooldown_frames {
+                0 => 0.0,
+                frames => player.dash_cooldown as f64 / frames as f64,
+            }
+        }
+        fn dash_left(player: &Player) -> f64 {
+            match player.config.dash_duration_frames {
+                0 => 0.0,
+                frames => player.dash_frames_remaining() as f64 / frames as f64,
+            }
+        }
+
+        let (own, other) = match side {
+            PlayerSide::Left => (&self.players.0, &self.players.1),
+            PlayerSide::Right => (&self.players.1, &self.players.0),
+        };
+        let (x_scale, y_scale) = (self.field.goal_line(), self.field.side_wall());
+        let velocity = self.frisbee.velocity() / (INITIAL_FRISBEE_SPEED * 0.1);
+        let possession = match self.frisbee.held_by_player {
+            Some(held_by) if held_by == side => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        };
+
+        let values = [
+            own.pos.x / x_scale,
+            own.pos.y / y_scale,
+            other.pos.x / x_scale,
+            other.pos.y / y_scale,
+            self.frisbee.pos.x / x_scale,
+            self.frisbee.pos.y / y_scale,
+            velocity.x,
+            velocity.y,
+            possession,
+            cooldown(own),
+            cooldown(other),
+            dash_left(own),
+            dash_left(other),
+        ];
+        let mut observation = [0.0; OBSERVATION_SIZE];
+        for (o, v) in observation.iter_mut().zip(values.iter()) {
+            *o = v.clamp(-1.0, 1.0) as f32;
+        }
+        observation
+    }
+
+    /// # Safety
+    ///
+    /// `out` must point to at least `OBSERVATION_SIZE` writable floats.
+    #[no_mangle]
+    pub unsafe extern "C" fn get_observation(&self, side: i8, out: *mut f32) {
+        let side = ::player::player_side_from_i8(side).unwrap_or(PlayerSide::Left);
+        let observation = self.observation(side);
+        ::std::ptr::copy_nonoverlapping(observation.as_ptr(), out, OBSERVATION_SIZE);
+    }
+
+    // Sets the input of `side` from a Q-table action index, for external learners driving the engine without an agent
+    // The input is what a human player agent would send, pass `inputs` to the next `epoch`
+    pub fn apply_action(&mut self, side: PlayerSide, action_index: u8) -> HumanIntent {
+        let input = ::agent::ActionSpace::to_human_intent(action_index as usize);
+        match side {
+            PlayerSide::Left => self.inputs.0 = input,
+            PlayerSide::Right => self.inputs.1 = input,
+        };
+        input
+    }
+
+    // Steps the engine with both players' action indices, whatever the agents are
+    pub fn step_with_actions(&mut self, left_index: u8, right_index: u8) {
+        let left = self.apply_action(PlayerSide::Left, left_index);
+        let right = self.apply_action(PlayerSide::Right, right_index);
+        let intents = (
+            ::agent::human_intent_to_intent(self, left, PlayerSide::Left),
+            ::agent::human_intent_to_intent(self, right, PlayerSide::Right),
+        );
+        self.step(intents);
+    }
+
+    // Same as `step_with_actions`, rewarding each side with the change in its lead unless `reward_fn` is set
+    // A round ends when the game leaves `Playing`, the wait before the next one isn't part of any episode
+    pub fn step_rl(&mut self, left_action: u8, right_action: u8) -> StepResult {
+        let was_playing = self.state_of_game == StateOfGame::Playing;
+        let scores = (self.players.0.score, self.players.1.score);
+        self.step_with_actions(left_action, right_action);
+
+        let (reward_left, reward_right) = if self.reward_fn.is_some() {
+            self.rewards
+        } else {
+            let left = (self.players.0.score - scores.0) as f32;
+            let right = (self.players.1.score - scores.1) as f32;
+            (left - right, right - left)
+        };
+        let done = self.is_over() || (was_playing && self.state_of_game != StateOfGame::Playing);
+        StepResult { reward_left, reward_right, done }
+    }
+
     pub fn step(&mut self, intents: (Intent, Intent)) {
+        if self.state_of_game == StateOfGame::Paused {
+            return;
+        }
+
         // Update timers
         let time_step = 1.0 / 60.0; // Assume we run at 60 frames per second
         self.time -= time_step;
@@ -239,13 +1244,26 @@ impl GameEngine {
         if self.players.0.score >= MAX_ROUND_POINTS ||
            self.players.1.score >= MAX_ROUND_POINTS ||
            self.time <= 0.0 {
-           self.state_of_game = StateOfGame::End;
+            self.state_of_game = match winner((self.players.0.score, self.players.1.score)) {
+                Some(side) => StateOfGame::Won(side),
+                None => StateOfGame::Draw,
+            };
         }
-        if self.state_of_game == StateOfGame::End {
+        if self.is_over() {
             // We don't need to update the rest if the game just ended
             return;
         }
 
+        if self.intent_history_capacity > 0 {
+            let capacity = self.intent_history_capacity;
+            for (history, intent) in [(&mut self.intent_history.0, intents.0), (&mut self.intent_history.1, intents.1)].iter_mut() {
+                if history.len() >= capacity {
+                    history.pop_front();
+                }
+                history.push_back(*intent);
+            }
+        }
+
         // Start the round after waiting a bit for players to reset their positions
         if self.state_of_game == StateOfGame::Start && self.start_time >= 1.0 {
             // Resume the game
@@ -261,8 +1279,7 @@ impl GameEngine {
                     }
                 },
                 None => {
-                    let mut rng = ::rand::thread_rng();
-                    if rng.gen_range(0.0, 1.0) < 0.5 {
+                    if self.rng.gen_range(0.0, 1.0) < 0.5 {
                         self.frisbee.last_held = Some(PlayerSide::Right);
                         &self.players.0
                     } else {
@@ -272,27 +1289,49 @@ impl GameEngine {
                 },
             };
             // Set direction so that the frisbee arrives in the player's hands
-            self.frisbee.direction = (target.pos + Vector2::new(target.get_horizontal_aim_direction(), 0.0) - self.frisbee.pos).normalized();
-            self.frisbee.speed = INITIAL_FRISBEE_SPEED;
+            let direction = (target.pos + Vector2::new(target.get_horizontal_aim_direction(), 0.0) - self.frisbee.pos).normalized();
+            self.frisbee.launch(direction, INITIAL_FRISBEE_SPEED);
+            self.frisbee.curve = 0.0;
+        }
+
+        // Returns true if the player grabbed the frisbee, a blocking player bounces it away instead
+        fn try_catch(player: &Player, frisbee: &mut Frisbee, intent: &Intent, state_of_game: &StateOfGame, catch_radius: f64, manual_catch: bool, block_mode: BlockMode) -> bool {
+            // We check the state of game to prevent grabbing the frisbee before it is initially thrown (Start state)
+            if *state_of_game == StateOfGame::Playing && frisbee.held_by_player.is_none()
+                && ::collision::player_collides_with_frisbee(player, frisbee, catch_radius) {
+                let catches = *intent == Intent::Grab || (block_mode == BlockMode::Catch && !manual_catch);
+                if catches {
+                    // Grab frisbee if the player collides with it
+                    frisbee.held_by_player = player.side;
+                    return true;
+                } else if block_mode == BlockMode::Bounce {
+                    // Always away from the player, so it can't bounce twice
+                    frisbee.direction.x = frisbee.direction.x.abs() * player.get_horizontal_aim_direction();
+                    frisbee.last_held = player.side;
+                }
+            }
+            false
         }
 
-        fn apply_action(player: &mut Player, frisbee: &mut Frisbee, intent: &Intent, state_of_game: &StateOfGame) -> ActionResult {
+        #[allow(clippy::too_many_arguments)]
+        fn apply_action(player: &mut Player, frisbee: &mut Frisbee, intent: &Intent, state_of_game: &StateOfGame, catch_radius: f64, manual_catch: bool, block_mode: BlockMode, throw_params: &ThrowParams) -> ActionResult {
             let mut res = ActionResult::None;
 
             match intent {
                 Intent::None => {},
                 Intent::Move(dir) => {
-                    if *state_of_game == StateOfGame::Playing {
-                        if player.slide.is_none() { // Cannot move while dashing
-                            match frisbee.held_by_player {
-                                // Cannot move while holding frisbee
-                                Some(held_by) if held_by == player.side.unwrap() => {},
-                                _ => {
-                                    player.pos += *dir * 0.1;
-                                    res = ActionResult::Moved;
-                                }
-                            };
-                        }
+                    // Cannot move while dashing
+                    if *state_of_game == StateOfGame::Playing && player.slide.is_none() {
+                        match frisbee.held_by_player {
+                            // Cannot move while holding frisbee
+                            Some(held_by) if held_by == player.side.unwrap() => {},
+                            _ => {
+                                // Shorter directions walk slower, longer ones don't go any faster
+                                let dir = if dir.length() > 1.0 { dir.normalized() } else { *dir };
+                                player.pos += dir * player.config.move_speed * 0.1;
+                                res = ActionResult::Moved;
+                            }
+                        };
                     }
                 },
                 Intent::Dash(dir) => {
@@ -305,36 +1344,30 @@ impl GameEngine {
                 Intent::Throw(dir) => {
                     match frisbee.held_by_player {
                         Some(held_by) if held_by == player.side.unwrap() => {
-                            frisbee.direction = player.get_throw_vector(dir);
-                            frisbee.speed = INITIAL_FRISBEE_SPEED;
+                            let power = throw_params.power(dir);
+                            let direction = (player.get_throw_vector(dir) + Vector2::new(0.0, power.vertical_bias)).normalized();
+                            frisbee.launch(direction, power.speed);
+                            frisbee.curve = dir.curve();
                             frisbee.last_held = frisbee.held_by_player;
                             frisbee.held_by_player = None;
                             res = ActionResult::Threw;
                         },
                         _ => {}
                     };
-                }
+                },
+                // Catching is checked below for every intent
+                Intent::Grab => {},
             };
 
-            if *state_of_game == StateOfGame::Playing {
-                // We check the state of game to prevent grabbing the frisbee before it is initially thrown (Start state)
-                match frisbee.held_by_player {
-                    None if ::collision::player_collides_with_frisbee(player, frisbee) => {
-                        // Grab frisbee if the player collides with it
-                        frisbee.held_by_player = player.side;
-                        res = ActionResult::GrabbedFrisbee;
-                    },
-                    _ => {}
-                };
+            if try_catch(player, frisbee, intent, state_of_game, catch_radius, manual_catch, block_mode) {
+                res = ActionResult::GrabbedFrisbee;
             }
-            if player.slide.is_some() {
-                let slide = player.slide.unwrap();
-                player.pos += slide.dir * 4.0 * 0.1;
-                if slide.has_reached_goal(&player.pos) {
-                    player.pos = slide.target;
-                    player.slide = None;
-                }
+            // Counted down before updating the slide so that a dash ending this frame waits the full cooldown
+            if player.dash_cooldown > 0 {
+                player.dash_cooldown -= 1;
             }
+            let carrying = frisbee.held_by_player.is_some() && frisbee.held_by_player == player.side;
+            player.update_slide(carrying);
 
             res
         }
@@ -358,15 +1391,11 @@ impl GameEngine {
             let a2 = engine.agents.1.take().unwrap();
 
             match engine.frisbee.last_held {
-                Some(PlayerSide::Left) => {
-                    if a2.get_type() == AgentType::TabularQLearning {
-                        engine.rewards.1 = -100.0;
-                    }
+                Some(PlayerSide::Left) if ::agent::uses_q_values(a2.get_type()) => {
+                    engine.rewards.1 = -100.0;
                 },
-                Some(PlayerSide::Right) => {
-                    if a1.get_type() == AgentType::TabularQLearning {
-                        engine.rewards.0 = -100.0;
-                    }
+                Some(PlayerSide::Right) if ::agent::uses_q_values(a1.get_type()) => {
+                    engine.rewards.0 = -100.0;
                 },
                 _ => {}
             };
@@ -375,17 +1404,27 @@ impl GameEngine {
             engine.q_scored = true;
         }
 
+        fn action_event(action_result: ActionResult, side: PlayerSide, intent: &Intent) -> Option<GameEvent> {
+            match (action_result, intent) {
+                (ActionResult::GrabbedFrisbee, _) => Some(GameEvent::Caught(side)),
+                (ActionResult::Threw, Intent::Throw(dir)) => Some(GameEvent::Thrown(side, *dir)),
+                _ => None,
+            }
+        }
+
         let a1 = self.agents.0.take().unwrap();
-        let res = apply_action(&mut self.players.0, &mut self.frisbee, &intents.0, &self.state_of_game);
-        if a1.get_type() == AgentType::TabularQLearning {
+        let res = apply_action(&mut self.players.0, &mut self.frisbee, &intents.0, &self.state_of_game, self.catch_radius, self.manual_catch, self.block_mode, &self.throw_params);
+        if ::agent::uses_q_values(a1.get_type()) {
             apply_action_rewards_to_q_agent(res, &mut self.rewards.0);
         }
+        self.events.extend(action_event(res, PlayerSide::Left, &intents.0));
 
         let a2 = self.agents.1.take().unwrap();
-        let res = apply_action(&mut self.players.1, &mut self.frisbee, &intents.1, &self.state_of_game);
-        if a2.get_type() == AgentType::TabularQLearning {
+        let res = apply_action(&mut self.players.1, &mut self.frisbee, &intents.1, &self.state_of_game, self.catch_radius, self.manual_catch, self.block_mode, &self.throw_params);
+        if ::agent::uses_q_values(a2.get_type()) {
             apply_action_rewards_to_q_agent(res, &mut self.rewards.1);
         }
+        self.events.extend(action_event(res, PlayerSide::Right, &intents.1));
 
         self.agents = (Some(a1), Some(a2));
 
@@ -397,34 +1436,230 @@ impl GameEngine {
                     PlayerSide::Right => self.frisbee.pos = self.players.1.pos
                 };
             },
-            None => {
-                if self.frisbee.speed != 0.0 {
-                    self.frisbee.pos += self.frisbee.direction * self.frisbee.speed * 0.1;
+            None if self.frisbee.speed != 0.0 => {
+                let substeps = self.substeps.max(1);
+                let dt = 1.0 / substeps as f64;
+                let mut bounced = false;
+                let mut caught = None;
+                for substep in 1..=substeps {
+                    bounced |= ::collision::frisbee_move(&mut self.frisbee, &self.field, dt);
+                    // Reaching the goal line inside the goal stops the frisbee there, it is scored below
+                    // The players get to catch it on the last position when they act on the next step
+                    if substep == substeps || self.frisbee.pos.x.abs() >= self.field.goal_line() {
+                        break;
+                    }
+                    caught = [PlayerSide::Left, PlayerSide::Right].iter().cloned().find(|&side| {
+                        let (player, intent) = match side {
+                            PlayerSide::Left => (&self.players.0, &intents.0),
+                            PlayerSide::Right => (&self.players.1, &intents.1),
+                        };
+                        try_catch(player, &mut self.frisbee, intent, &self.state_of_game, self.catch_radius, self.manual_catch, self.block_mode)
+                    });
+                    if caught.is_some() {
+                        break;
+                    }
+                }
+                if bounced {
+                    self.events.push(GameEvent::WallBounce);
+                }
+                if let Some(side) = caught {
+                    let (player, agent, reward) = match side {
+                        PlayerSide::Left => (&self.players.0, &self.agents.0, &mut self.rewards.0),
+                        PlayerSide::Right => (&self.players.1, &self.agents.1, &mut self.rewards.1),
+                    };
+                    self.frisbee.pos = player.pos;
+                    if agent.as_ref().is_some_and(|a| ::agent::uses_q_values(a.get_type())) {
+                        apply_action_rewards_to_q_agent(ActionResult::GrabbedFrisbee, reward);
+                    }
+                    self.events.push(GameEvent::Caught(side));
                 }
             },
+            None => {},
         };
 
-        let collided = ::collision::player_collision(&mut self.players.0);
+        let net = self.net();
+        let collided = ::collision::player_collision(&mut self.players.0, &self.field, net);
         if collided {
             // Cancels slide if the player hits an obstacle to prevent being stuck
-            self.players.0.slide = None;
+            self.players.0.end_slide();
         }
-        let collided = ::collision::player_collision(&mut self.players.1);
+        let collided = ::collision::player_collision(&mut self.players.1, &self.field, net);
         if collided {
             // Cancels slide if the player hits an obstacle to prevent being stuck
-            self.players.1.slide = None;
+            self.players.1.end_slide();
+        }
+        if self.player_collision && ::collision::separate_players(&mut self.players) {
+            // Pushed back inside the field if need be
+            for player in [&mut self.players.0, &mut self.players.1].iter_mut() {
+                ::collision::player_collision(player, &self.field, net);
+                player.end_slide();
+            }
         }
 
-        ::collision::frisbee_collision_wall(&mut self.frisbee);
-        let goal = ::collision::frisbee_collision_goal(&mut self.frisbee, &mut self.players);
+        let goal = ::collision::frisbee_collision_goal(&mut self.frisbee, &mut self.players, &self.scoring_zones, &self.field);
         if goal {
+            if let Some(scorer) = self.frisbee.last_held {
+                self.events.push(GameEvent::Scored(scorer));
+            }
             self.state_of_game = StateOfGame::Start;
             self.start_time = 0.0;
-            self.players.0.dash_to_pos(Vector2::new(-9.0, 0.0));
-            self.players.1.dash_to_pos(Vector2::new(9.0, 0.0));
+            self.players.0.dash_to_pos(self.field.spawn(PlayerSide::Left));
+            self.players.1.dash_to_pos(self.field.spawn(PlayerSide::Right));
 
             reward_q_for_goal(self);
         }
+
+        if let Some(ref reward_fn) = self.reward_fn {
+            self.rewards = (reward_fn(self, PlayerSide::Left), reward_fn(self, PlayerSide::Right));
+        }
+
+        self.frame += 1;
+        if let Some(max_frames) = self.max_frames {
+            if self.frame >= max_frames {
+                self.state_of_game = StateOfGame::Draw;
+            }
+        }
+
+        // Players wait still between rounds, only the frames in play count
+        if let Some(stalemate) = self.stalemate {
+            if self.state_of_game == StateOfGame::Playing {
+                if self.hash_history.len() >= stalemate.window {
+                    self.hash_history.pop_front();
+                }
+                let hash = self.hash_both();
+                self.hash_history.push_back(hash);
+                if self.is_stalemate() {
+                    self.state_of_game = StateOfGame::Draw;
+                }
+            }
+        }
+    }
+
+    // True if the last state in play came back more than `repeats` times within the window
+    // Only the last hash needs counting, any other one would have tripped it when it was recorded
+    pub fn is_stalemate(&self) -> bool {
+        match (self.stalemate, self.hash_history.back()) {
+            (Some(stalemate), Some(last)) => self.hash_history.iter().filter(|&h| h == last).count() > stalemate.repeats,
+            _ => false,
+        }
+    }
+
+    // Events since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        ::std::mem::take(&mut self.events)
+    }
+
+    // False while the player on `side` is dashing or waiting for its dash cooldown
+    pub fn can_dash(&self, side: PlayerSide) -> bool {
+        match side {
+            PlayerSide::Left => self.players.0.can_dash(),
+            PlayerSide::Right => self.players.1.can_dash(),
+        }
+    }
+
+    // Frames the dash of the player on `side` still moves for, 0 when not dashing
+    pub fn dash_frames_remaining(&self, side: PlayerSide) -> u32 {
+        match side {
+            PlayerSide::Left => self.players.0.dash_frames_remaining(),
+            PlayerSide::Right => self.players.1.dash_frames_remaining(),
+        }
+    }
+
+    // Whether the player on `side` would grab the frisbee where it stands, as checked after each action
+    // With `manual_catch` it still has to play `Intent::Grab`
+    pub fn can_catch(&self, side: PlayerSide) -> bool {
+        let player = match side {
+            PlayerSide::Left => &self.players.0,
+            PlayerSide::Right => &self.players.1,
+        };
+        self.state_of_game == StateOfGame::Playing
+            && self.frisbee.held_by_player.is_none()
+            && ::collision::player_collides_with_frisbee(player, &self.frisbee, self.catch_radius)
+    }
+
+    // Frames stepped since the last reset, frozen once the match is over
+    pub fn frames_elapsed(&self) -> u64 {
+        self.frame
+    }
+
+    // Freezes the match until it is resumed, a match already over can't be paused
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused {
+            if self.state_of_game != StateOfGame::Paused && !self.is_over() {
+                self.paused_from = Some(self.state_of_game);
+                self.state_of_game = StateOfGame::Paused;
+            }
+        } else if let Some(state) = self.paused_from.take() {
+            self.state_of_game = state;
+        }
+    }
+
+    // The field seen from above for terminal debugging, a row per unit of height and two columns per unit of width
+    // `L` and `R` are the players, `*` the frisbee when nobody holds it, the last line is the score
+    pub fn render_ascii(&self) -> String {
+        let rows = (self.field.height.ceil() as usize).max(1);
+        let cols = ((self.field.width * 2.0).ceil() as usize).max(1);
+        let cell = |pos: &Vector2| {
+            let col = ((pos.x + self.field.goal_line()) / self.field.width * cols as f64).floor();
+            let row = ((self.field.side_wall() - pos.y) / self.field.height * rows as f64).floor();
+            (row.max(0.0).min(rows as f64 - 1.0) as usize, col.max(0.0).min(cols as f64 - 1.0) as usize)
+        };
+
+        let mut grid = vec![vec![' '; cols]; rows];
+        if self.frisbee.held_by_player.is_none() {
+            let (row, col) = cell(&self.frisbee.pos);
+            grid[row][col] = '*';
+        }
+        for (player, marker) in [(&self.players.0, 'L'), (&self.players.1, 'R')].iter() {
+            let (row, col) = cell(&player.pos);
+            grid[row][col] = *marker;
+        }
+
+        let border = format!("+{}+", "-".repeat(cols));
+        let mut out = border.clone();
+        out.push('\n');
+        for row in grid.iter() {
+            out.push('|');
+            out.extend(row.iter());
+            out.push_str("|\n");
+        }
+        out.push_str(&border);
+        out.push('\n');
+        out.push_str(&format!("L {} - {} R", self.players.0.score, self.players.1.score));
+        out
+    }
+
+    // Positions of the frisbee after each of the next `frames` steps if no player touches it, the engine is left as is
+    // A held frisbee stays where it is, one reaching a goal stays on the goal line where the real one would be scored
+    pub fn predict_frisbee(&self, frames: u32) -> Vec<Vector2> {
+        let mut frisbee = self.frisbee;
+        let mut positions = Vec::with_capacity(frames as usize);
+        for _ in 0..frames {
+            if frisbee.held_by_player.is_none() && frisbee.speed != 0.0 {
+                ::collision::frisbee_move_substeps(&mut frisbee, &self.field, self.substeps);
+                ::collision::frisbee_back_wall_bounce(&mut frisbee, &self.field);
+            }
+            positions.push(frisbee.pos);
+        }
+        positions
+    }
+
+    // Points of the best scoring zone, the most a single goal can score
+    pub fn max_goal_points(&self) -> i8 {
+        self.scoring_zones.iter().map(|zone| zone.points).max().unwrap_or(0)
+    }
+
+    // True when being close to a free frisbee isn't enough to catch it
+    pub fn requires_grab(&self) -> bool {
+        self.manual_catch || self.block_mode != BlockMode::Catch
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state_of_game == StateOfGame::Paused
+    }
+
+    pub fn is_over(&self) -> bool {
+        matches!(self.state_of_game, StateOfGame::Won(_) | StateOfGame::Draw)
     }
 
     pub fn to_shared_data(&self, shared: &mut SharedData) {
@@ -448,6 +1683,17 @@ impl GameEngine {
     }
 
     pub fn hash(&self) -> u64 {
+        self.hash_canonical(PlayerSide::Left)
+    }
+
+    // Hash of the state seen from both sides, unlike `hash` it tells apart the positions of the right player
+    pub fn hash_both(&self) -> u64 {
+        self.hash_canonical(PlayerSide::Left) * ::agent::QVALUES_MAX_STATES + self.hash_canonical(PlayerSide::Right)
+    }
+
+    // State hash seen from `side`: for the right player the field is mirrored so it plays on the left,
+    // a situation and its mirror share their key and one Q-table can serve both sides
+    pub fn hash_canonical(&self, side: PlayerSide) -> u64 {
         fn set_state(hash: &mut u64, val: f64, min: i64, max: i64, scale: f64, amplitudes: &mut Vec<u32>, max_value: &mut u64) {
             fn discretize(val: f64, min: i64, max: i64, scale: f64) -> (u32, u32) {
                 let min = min as f64 * scale;
@@ -492,23 +1738,41 @@ impl GameEngine {
         let mut max_value = 0;
         let mut amplitudes: Vec<u32> = Vec::new();
 
+        // Whether the frisbee flies back toward the player's goal (0), stays put or is held (1) or flies toward the opponent's (2)
+        // The direction's angle alone can't tell a frisbee from one moving the opposite way
+        fn discretize_frisbee_heading(velocity_x: f64) -> f64 {
+            if velocity_x < 0.0 {
+                0.0
+            } else if velocity_x > 0.0 {
+                2.0
+            } else {
+                1.0
+            }
+        }
+
+        let (player_pos, frisbee_pos, direction, velocity) = match side {
+            PlayerSide::Left => (self.players.0.pos, self.frisbee.pos, self.frisbee.direction, self.frisbee.velocity()),
+            PlayerSide::Right => {
+                let mirror = |v: Vector2| Vector2::new(-v.x, v.y);
+                (mirror(self.players.1.pos), mirror(self.frisbee.pos), mirror(self.frisbee.direction), mirror(self.frisbee.velocity()))
+            },
+        };
+
         let scale = 1.0;
-        set_state(&mut val, self.players.0.pos.x, -9, -1, scale, &mut amplitudes, &mut max_value);
-        set_state(&mut val, self.players.0.pos.y, -5, 5, scale, &mut amplitudes, &mut max_value);
+        set_state(&mut val, player_pos.x, -9, -1, scale, &mut amplitudes, &mut max_value);
+        set_state(&mut val, player_pos.y, -5, 5, scale, &mut amplitudes, &mut max_value);
 
-        set_state(&mut val, self.frisbee.pos.x, -9, 9, scale, &mut amplitudes, &mut max_value);
-        set_state(&mut val, self.frisbee.pos.y, -5, 5, scale, &mut amplitudes, &mut max_value);
+        set_state(&mut val, frisbee_pos.x, -9, 9, scale, &mut amplitudes, &mut max_value);
+        set_state(&mut val, frisbee_pos.y, -5, 5, scale, &mut amplitudes, &mut max_value);
 
-        set_state(&mut val, match self.frisbee.last_held {
-            Some(side) => match side {
-                PlayerSide::Left => 1.0,
-                PlayerSide::Right => 0.0,
-            },
-            None => 0.0
-        }, 0, 1, 1.0, &mut amplitudes, &mut max_value);
+        set_state(&mut val, if self.frisbee.last_held == Some(side) { 1.0 } else { 0.0 }, 0, 1, 1.0, &mut amplitudes, &mut max_value);
 
-        set_state(&mut val, discretize_frisbee_direction(self.frisbee.direction), 0, 4, 1.0, &mut amplitudes, &mut max_value);
+        set_state(&mut val, discretize_frisbee_direction(direction), 0, 4, 1.0, &mut amplitudes, &mut max_value);
+        set_state(&mut val, discretize_frisbee_heading(velocity.x), 0, 2, 1.0, &mut amplitudes, &mut max_value);
 
+        // Every component wraps around its amplitude, a hash out of range means the bound is out of date
+        debug_assert_eq!(max_value + 1, QVALUES_MAX_STATES);
+        debug_assert!(val < QVALUES_MAX_STATES);
         val
     }
 }
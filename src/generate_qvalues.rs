@@ -1,18 +1,8 @@
 extern crate rustjammers_engine;
-extern crate bincode;
 
 use rustjammers_engine::agent;
 use rustjammers_engine::game_engine;
-
-fn max(arr: &[f32; agent::QVALUES_ACTIONS]) -> f32 {
-    let mut max = std::f32::MIN;
-    for x in arr {
-        if max < *x {
-            max = *x;
-        }
-    }
-    max
-}
+use rustjammers_engine::player::PlayerSide;
 
 fn progress_bar(count: i32, total: i32, text_current: &str, text_total: &str) {
     let bar_len = 30;
@@ -25,7 +15,7 @@ fn progress_bar(count: i32, total: i32, text_current: &str, text_total: &str) {
     print!("\r[{}] {}% ({} / {}) ", bar, percents, text_current, text_total);
 
     if count >= total {
-        println!("");
+        println!();
     }
 }
 
@@ -58,7 +48,7 @@ fn main() {
             run_mode = RunMode::Time;
             let mut split: Vec<&str> = arg.split(':').collect();
             split.reverse();
-            if split.len() > 0 {
+            if !split.is_empty() {
                 duration_seconds += split[0].parse::<u64>().unwrap();
             }
             if split.len() > 1 {
@@ -84,8 +74,6 @@ fn main() {
     }
 
     let mut engine = game_engine::GameEngine::new();
-    let discounting_rate = 0.95f32;
-    let learning_rate = 0.8f32;
 
     let min_explo_rate: f32 = 0.05;
     let max_explo_rate: f32 = 1.0;
@@ -116,7 +104,7 @@ fn main() {
         };
 
         engine.reset();
-        while engine.state_of_game != game_engine::StateOfGame::End {
+        while !engine.is_over() {
             let state = engine.hash();
 
             engine.epoch(::agent::HumanIntent::IDLE, ::agent::HumanIntent::IDLE);
@@ -128,8 +116,9 @@ fn main() {
 
             // Update Q-Values
             let new_state = engine.hash();
-            engine.q_values.get_mut(&state).unwrap().0[actions.0] = engine.q_values[&state].0[actions.0] + learning_rate * (engine.rewards.0 + discounting_rate * max(&engine.q_values[&new_state].0) - engine.q_values[&state].0[actions.0]);
-            //engine.q_values.get_mut(&state).unwrap().1[actions.1] = engine.q_values[&state].1[actions.1] + learning_rate * (engine.rewards.1 + discounting_rate * max(&engine.q_values[&new_state].1) - engine.q_values[&state].1[actions.1]);
+            let rewards = engine.rewards;
+            engine.update_q_value(PlayerSide::Left, state, actions.0, rewards.0, new_state);
+            //engine.update_q_value(PlayerSide::Right, state, actions.1, rewards.1, new_state);
 
             if engine.q_scored {
                 break;
@@ -153,14 +142,13 @@ fn main() {
 
     // Save Q-Values
     println!("Saving Q-values...");
-    let encoded = bincode::serialize(&engine.q_values).expect("Could not encode Q-Values to binary");
     let mut path = ::std::env::current_dir().unwrap();
     path.push(::std::path::PathBuf::from("Unity"));
     if !path.exists() {
         path = ::std::env::current_dir().unwrap();
     }
     path.push(::std::path::PathBuf::from("q_values.bin"));
-    std::fs::write(path.clone(), encoded).expect("Unable to write Q-values.");
+    engine.save_q_values(path.to_str().expect("Invalid Q-values path.")).expect("Unable to write Q-values.");
 
     println!("Done!\r\nSaved Q-values to \"{}\".", path.display());
 }
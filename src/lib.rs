@@ -1,15 +1,24 @@
 extern crate rand;
 #[macro_use]
 extern crate bitflags;
-extern crate bincode;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 pub mod agent;
-mod player;
+pub mod player;
 mod frisbee;
 mod vector2;
 mod collision;
 mod shared_data;
 pub mod game_engine;
+pub mod step_controller;
+pub mod replay;
+pub mod sim;
 
 #[test]
 fn test_dijkstra() {
@@ -20,10 +29,10 @@ fn test_dijkstra() {
     test.send_type_p2(agent::AgentType::Dijkstra as i8, 1000.0, 3);
     loop {
 
-        println!("STEP: {}", step.to_string());
+        println!("STEP: {}", step);
         test.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
 
-        if test.state_of_game == game_engine::StateOfGame::End {
+        if test.is_over() {
             break;
         }
         step+=1;
@@ -31,3 +40,2852 @@ fn test_dijkstra() {
 
     }
 }
+
+#[test]
+fn test_step_controller() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    let mut controller = step_controller::StepController::new(engine);
+
+    controller.pause();
+    assert!(controller.tick(agent::HumanIntent::UP, agent::HumanIntent::IDLE).is_none());
+    assert_eq!(controller.frame, 0);
+    assert!(controller.history.is_empty());
+
+    let y = controller.engine.players.0.pos.y;
+    let record = controller.advance(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    assert_eq!(record.frame, 1);
+    assert_eq!(record.state.p1_y, y);
+    assert!(controller.is_paused());
+
+    controller.resume();
+    for _ in 0..10 {
+        assert!(controller.tick(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE).is_some());
+    }
+    assert_eq!(controller.frame, 11);
+    assert_eq!(controller.history.len(), 11);
+    assert_eq!(controller.last_record().unwrap().state.time, controller.engine.time);
+}
+
+#[test]
+fn test_scoring_zones() {
+    fn score_throw_at(engine: &mut game_engine::GameEngine, y: f64) -> i8 {
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        // Keep the right player away from the frisbee so it can't catch it
+        engine.players.1.pos = vector2::Vector2::new(1.0, 0.0);
+        engine.frisbee.pos = vector2::Vector2::new(9.8, y);
+        engine.frisbee.direction = vector2::Vector2::new(1.0, 0.0);
+        engine.frisbee.speed = game_engine::INITIAL_FRISBEE_SPEED;
+        engine.frisbee.last_held = Some(player::PlayerSide::Left);
+        engine.step((agent::Intent::None, agent::Intent::None));
+        engine.players.0.score
+    }
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+
+    // Default layout
+    assert_eq!(score_throw_at(&mut engine, 0.0), 5);
+    assert_eq!(score_throw_at(&mut engine, 3.0), 3);
+
+    engine.scoring_zones = vec![
+        game_engine::ScoringZone::new((-1.0, 1.0), 1),
+        game_engine::ScoringZone::new((f64::MIN, f64::MAX), 2),
+    ];
+    assert_eq!(score_throw_at(&mut engine, 0.0), 1);
+    assert_eq!(score_throw_at(&mut engine, 3.0), 2);
+    assert_eq!(score_throw_at(&mut engine, -3.0), 2);
+}
+
+#[test]
+fn test_ask_agent() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    engine.explo_rate = 0.0;
+    let mut values = [0.0; agent::QVALUES_ACTIONS];
+    values[agent::human_intent_to_index(agent::HumanIntent::UP) as usize] = 1.0;
+    engine.q_values.insert(engine.hash(), (values, values));
+
+    let mut q_agent = agent::TabularQLearningAgent::new();
+    match engine.ask_agent(&mut q_agent, player::PlayerSide::Left) {
+        agent::Intent::Move(dir) => assert_eq!(dir, vector2::Vector2::new(0.0, 1.0)),
+        intent => panic!("Unexpected intent {:?}", intent),
+    };
+    // The Q-learner writes its choice to the inputs of the copy only
+    assert_eq!(engine.inputs.0, agent::HumanIntent::IDLE);
+    assert_eq!(engine.players.0.pos, vector2::Vector2::new(-9.0, 0.0));
+}
+
+#[test]
+fn test_minimax_finds_goal() {
+    fn goal_setup() -> game_engine::GameEngine {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        // The left player holds the frisbee right in front of the opponent's goal
+        engine.players.0.pos = vector2::Vector2::new(9.7, 0.0);
+        engine.players.1.pos = vector2::Vector2::new(9.0, 3.0);
+        engine.frisbee.pos = engine.players.0.pos;
+        engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+        engine
+    }
+
+    for depth in 1..3 {
+        let mut engine = goal_setup();
+        let mut minimax = agent::MinimaxAgent::new(depth);
+        let intent = agent::Agent::act(&mut minimax, player::PlayerSide::Left, &mut engine);
+        match intent {
+            agent::Intent::Throw(_) => {},
+            _ => panic!("Expected a throw, got {:?}", intent),
+        };
+
+        engine.step((intent, agent::Intent::None));
+        for _ in 1..depth {
+            engine.step((agent::Intent::None, agent::Intent::None));
+        }
+        assert!(engine.players.0.score > 0, "Depth {} did not score with {:?}", depth, intent);
+    }
+}
+
+#[test]
+fn test_mcts_deterministic() {
+    fn setup() -> game_engine::GameEngine {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.players.0.pos = vector2::Vector2::new(9.7, 0.0);
+        engine.players.1.pos = vector2::Vector2::new(9.0, 3.0);
+        engine.frisbee.pos = engine.players.0.pos;
+        engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+        engine
+    }
+
+    let mut intents = Vec::new();
+    for _ in 0..2 {
+        let mut engine = setup();
+        let mut mcts = agent::MCTSAgent::new(200, 1.4, 42);
+        intents.push(agent::Agent::act(&mut mcts, player::PlayerSide::Left, &mut engine));
+    }
+    assert_eq!(format!("{:?}", intents[0]), format!("{:?}", intents[1]));
+
+    // The most visited throw has to score
+    let mut engine = setup();
+    engine.step((intents[0], agent::Intent::None));
+    for _ in 0..10 {
+        engine.step((agent::Intent::None, agent::Intent::None));
+    }
+    assert!(engine.players.0.score > 0, "{:?} did not score", intents[0]);
+}
+
+#[test]
+fn test_sarsa_update() {
+    let mut q_values = agent::QValues::new();
+    let mut next = [0.0; agent::QVALUES_ACTIONS];
+    next[3] = 10.0;
+    next[4] = 100.0; // Greedy action, must not be used by SARSA
+    q_values.insert(2, ([0.0; agent::QVALUES_ACTIONS], next));
+
+    agent::sarsa_update(&mut q_values, player::PlayerSide::Right, 1, 5, 1.0, 2, 3, 0.5, 0.9);
+    // 0 + 0.5 * (1 + 0.9 * 10 - 0)
+    assert_eq!(q_values[&1].1[5], 5.0);
+    assert_eq!(q_values[&1].0[5], 0.0);
+
+    // A second identical transition keeps moving toward the target of 10
+    agent::sarsa_update(&mut q_values, player::PlayerSide::Right, 1, 5, 1.0, 2, 3, 0.5, 0.9);
+    assert_eq!(q_values[&1].1[5], 7.5);
+}
+
+#[test]
+fn test_q_values_save_load() {
+    let path = ::std::env::temp_dir().join("rustjammers_test_q_values.bin");
+    let path = path.to_str().unwrap();
+
+    let mut engine = game_engine::GameEngine::new();
+    for i in 0..100 {
+        engine.q_values.insert(i, ([0.0; agent::QVALUES_ACTIONS], [0.0; agent::QVALUES_ACTIONS]));
+    }
+    engine.q_values.get_mut(&3).unwrap().0[2] = 1.5;
+    engine.q_values.get_mut(&42).unwrap().1[16] = -100.0;
+    engine.q_values.get_mut(&99).unwrap().0[0] = 0.125;
+    engine.save_q_values(path).unwrap();
+
+    let mut loaded = game_engine::GameEngine::new();
+    loaded.load_q_values(path).unwrap();
+    assert_eq!(loaded.q_values, engine.q_values);
+
+    // Files made for another action count are rejected and leave the table untouched
+    let mut bytes = ::std::fs::read(path).unwrap();
+    bytes[8] = (agent::QVALUES_ACTIONS + 1) as u8;
+    ::std::fs::write(path, bytes).unwrap();
+    let mut rejected = game_engine::GameEngine::new();
+    assert!(rejected.load_q_values(path).is_err());
+    assert!(rejected.q_values.is_empty());
+
+    // A header claiming more states than the file holds is an error, nothing is allocated for them
+    let mut truncated = u64::MAX.to_le_bytes().to_vec();
+    truncated.extend_from_slice(&(agent::QVALUES_ACTIONS as u64).to_le_bytes());
+    ::std::fs::write(path, truncated).unwrap();
+    assert!(rejected.load_q_values(path).is_err());
+    assert!(rejected.q_values.is_empty());
+
+    ::std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_hyperparameters() {
+    let mut engine = game_engine::GameEngine::new();
+    assert!(engine.set_hyperparameters(1.5, 0.5).is_err());
+    assert!(engine.set_hyperparameters(0.5, -0.1).is_err());
+    assert_eq!(engine.learning_rate, 0.8);
+    assert_eq!(engine.discount_factor, 0.95);
+
+    engine.set_hyperparameters(0.5, 0.5).unwrap();
+    let mut next = [0.0; agent::QVALUES_ACTIONS];
+    next[7] = 4.0;
+    engine.q_values.insert(2, (next, next));
+    engine.update_q_value(player::PlayerSide::Left, 1, 0, 1.0, 2);
+    // 0 + 0.5 * (1 + 0.5 * 4 - 0)
+    assert_eq!(engine.q_values[&1].0[0], 1.5);
+}
+
+#[test]
+fn test_epsilon_schedule() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.set_epsilon_schedule(1.0, 0.1, 10);
+    assert_eq!(engine.explo_rate, 1.0);
+    for _ in 0..5 {
+        engine.reset();
+    }
+    assert!((engine.explo_rate - 0.55).abs() < 1e-6);
+    for _ in 0..5 {
+        engine.reset();
+    }
+    assert_eq!(engine.explo_rate, 0.1);
+    engine.reset();
+    assert_eq!(engine.explo_rate, 0.1);
+
+    // Training on episode resets decays it all the same
+    engine.set_epsilon_schedule(1.0, 0.1, 10);
+    for _ in 0..5 {
+        engine.reset_episode();
+    }
+    assert!((engine.explo_rate - 0.55).abs() < 1e-6);
+
+    engine.set_epsilon_schedule_with(1.0, 0.0, 10, game_engine::EpsilonDecay::Exponential);
+    let mut previous = engine.explo_rate;
+    for _ in 0..10 {
+        engine.reset();
+        assert!(engine.explo_rate < previous);
+        previous = engine.explo_rate;
+    }
+    assert_eq!(engine.explo_rate, 0.0);
+}
+
+#[test]
+fn test_seeded_rng() {
+    fn record_intents(seed: u64) -> Vec<String> {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::Random as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+        engine.reset();
+        engine.seed_rng(seed);
+
+        let mut intents = Vec::new();
+        for _ in 0..600 {
+            let frame = engine.select_intents(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+            intents.push(format!("{:?}", frame));
+            engine.step(frame);
+        }
+        intents
+    }
+
+    assert_eq!(record_intents(7), record_intents(7));
+    assert_ne!(record_intents(7), record_intents(8));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::Random as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.reset();
+    engine.seed_rng(3);
+    for _ in 0..200 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+
+    let json = serde_json::to_string(&engine).unwrap();
+    let mut restored: game_engine::GameEngine = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+
+    // Agents are not serialized, drive both engines with the same intents
+    for e in [&mut engine, &mut restored].iter_mut() {
+        e.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        e.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    }
+    for i in 0..60 {
+        let input = agent::human_intent_from_index((i % agent::QVALUES_ACTIONS) as u8);
+        engine.epoch(input, agent::HumanIntent::DOWN);
+        restored.epoch(input, agent::HumanIntent::DOWN);
+        assert_eq!(serde_json::to_string(&restored).unwrap(), serde_json::to_string(&engine).unwrap());
+    }
+}
+
+#[test]
+fn test_replay() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::Random as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.reset();
+    engine.seed_rng(11);
+
+    let mut recorder = replay::Recorder::new();
+    recorder.start_recording();
+    while !engine.is_over() {
+        recorder.epoch(&mut engine, agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    recorder.stop_recording();
+
+    let path = ::std::env::temp_dir().join("rustjammers_test_replay.bin");
+    let path = path.to_str().unwrap();
+    recorder.save(path).unwrap();
+
+    // A different seed must not matter, only the recorded intents drive the replay
+    let mut replayer = replay::Replayer::load(path).unwrap();
+    let mut replayed = replayer.new_engine(12);
+    assert_eq!(replayer.play(&mut replayed), recorder.frames.len());
+    assert!(replayed.is_over());
+    assert!(replayed.state_of_game == engine.state_of_game);
+    assert_eq!(replayed.players.0.score, engine.players.0.score);
+    assert_eq!(replayed.players.1.score, engine.players.1.score);
+    assert_eq!(replayed.players.0.pos, engine.players.0.pos);
+    assert_eq!(replayed.players.1.pos, engine.players.1.pos);
+
+    // A frame count the file can't hold is rejected before anything is allocated
+    let mut corrupt = vec![0u8];
+    corrupt.extend_from_slice(&u64::MAX.to_le_bytes());
+    ::std::fs::write(path, corrupt).unwrap();
+    match replay::Replayer::load(path) {
+        Err(e) => assert_eq!(e.kind(), ::std::io::ErrorKind::InvalidData),
+        Ok(_) => panic!("A corrupt replay was loaded."),
+    }
+
+    ::std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_copy_in_reuses_scratch() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::Random as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.reset();
+    engine.seed_rng(5);
+
+    let mut scratch = game_engine::GameEngine::new();
+    scratch.q_values.insert(42, ([0.5; agent::QVALUES_ACTIONS], [0.5; agent::QVALUES_ACTIONS]));
+    let capacity = scratch.q_values.capacity();
+
+    for _ in 0..10000 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+        engine.copy_in(&mut scratch);
+    }
+
+    let mut reference = game_engine::GameEngine::new();
+    engine.copy_in(&mut reference);
+    assert_eq!(scratch.players.0.pos, reference.players.0.pos);
+    assert_eq!(scratch.players.1.pos, reference.players.1.pos);
+    assert_eq!(scratch.players.0.score, reference.players.0.score);
+    assert_eq!(scratch.players.1.score, reference.players.1.score);
+    assert_eq!(scratch.frisbee.pos, reference.frisbee.pos);
+    assert_eq!(scratch.time, reference.time);
+    assert!(scratch.state_of_game == reference.state_of_game);
+
+    // The target's Q-table is neither replaced nor reallocated
+    assert_eq!(scratch.q_values.len(), 1);
+    assert_eq!(scratch.q_values.capacity(), capacity);
+}
+
+#[test]
+fn test_dijkstra_depth() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    // The frisbee stays in the right player's hands, so only the left player's choices branch
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+    engine.frisbee.pos = engine.players.1.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Right);
+
+    let agent = agent::DijkstraAgent::new(3);
+    let nodes = agent.search(player::PlayerSide::Left, &engine);
+
+    // The root, then one node per first intent before and after it is applied
+    // Only moves keep branching since a dashing player can't act on the next step
+    let first = 16;
+    let second = 8 * 16;
+    let third = 8 * 8 * 16;
+    assert_eq!(nodes.len(), 1 + first + first + second + third);
+}
+
+#[test]
+fn test_astar_expands_less_than_dijkstra() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    // The frisbee lies still in front of the left player
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+    engine.frisbee.pos = vector2::Vector2::new(-3.0, 1.0);
+    engine.frisbee.speed = 0.0;
+    engine.frisbee.last_held = Some(player::PlayerSide::Right);
+
+    let astar = agent::AStarAgent::new(10000).search(player::PlayerSide::Left, &engine);
+    assert!(astar.reached_goal);
+    match astar.intent {
+        agent::Intent::Move(dir) | agent::Intent::Dash(dir) => assert!(dir.x > 0.0 && dir.y > 0.0),
+        _ => panic!("Unexpected intent {:?}", astar.intent),
+    }
+
+    let dijkstra = agent::DijkstraAgent::new(3).search(player::PlayerSide::Left, &engine);
+    assert!(astar.expanded < dijkstra.len());
+}
+
+#[test]
+fn test_legal_intents() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+    let holding = engine.legal_intents(player::PlayerSide::Left);
+    assert_eq!(holding.len(), 5);
+    assert!(holding.iter().all(|i| matches!(i, agent::Intent::Throw(_))));
+
+    // The other side is free and not dashing
+    let free = engine.legal_intents(player::PlayerSide::Right);
+    assert_eq!(free.len(), 16);
+    assert!(free.iter().all(|i| matches!(i, agent::Intent::Move(_) | agent::Intent::Dash(_))));
+
+    engine.players.1.dash(vector2::Vector2::new(0.0, 2.5));
+    assert!(engine.legal_intents(player::PlayerSide::Right).is_empty());
+}
+
+#[test]
+fn test_flat_throws() {
+    let sides = [(player::PlayerSide::Left, -5.0), (player::PlayerSide::Right, 5.0)];
+    let inputs = [(agent::HumanIntent::LEFT, -1.0), (agent::HumanIntent::RIGHT, 1.0)];
+    for (side, x) in sides.iter() {
+        for (input, expected) in inputs.iter() {
+            let mut engine = game_engine::GameEngine::new();
+            engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+            engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+            engine.reset();
+            engine.state_of_game = game_engine::StateOfGame::Playing;
+            engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+            engine.players.1.pos = vector2::Vector2::new(5.0, 0.0);
+            engine.frisbee.pos = vector2::Vector2::new(*x, 0.0);
+            engine.frisbee.held_by_player = Some(*side);
+
+            let intent = agent::human_intent_to_intent(&engine, *input | agent::HumanIntent::THROW, *side);
+            match intent {
+                agent::Intent::Throw(frisbee::ThrowDirection::Custom(dir)) => assert_eq!(dir, vector2::Vector2::new(*expected, 0.0)),
+                _ => panic!("Expected a flat throw, got {:?}", intent),
+            }
+
+            let intents = match side {
+                player::PlayerSide::Left => (intent, agent::Intent::None),
+                player::PlayerSide::Right => (agent::Intent::None, intent),
+            };
+            engine.step(intents);
+            assert!(engine.frisbee.held_by_player.is_none());
+            assert_eq!(engine.frisbee.direction, vector2::Vector2::new(*expected, 0.0));
+        }
+    }
+}
+
+#[test]
+fn test_vector_angles() {
+    use std::f64::consts::{ FRAC_PI_2, PI };
+
+    let rotated = vector2::Vector2::new(1.0, 0.0).rotate(FRAC_PI_2);
+    assert!((rotated.x - 0.0).abs() < 1e-9);
+    assert!((rotated.y - 1.0).abs() < 1e-9);
+    assert!((rotated.angle() - FRAC_PI_2).abs() < 1e-9);
+
+    let a = vector2::Vector2::new(1.0, 1.0);
+    let b = vector2::Vector2::new(-2.0, 0.5);
+    assert!((a.angle_between(&b) - b.angle_between(&a)).abs() < 1e-12);
+    assert!((vector2::Vector2::new(1.0, 0.0).angle_between(&vector2::Vector2::new(-3.0, 0.0)) - PI).abs() < 1e-9);
+
+    // No NaN on zero vectors
+    let zero = vector2::Vector2::zero();
+    assert_eq!(zero.angle(), 0.0);
+    assert_eq!(zero.angle_between(&a), 0.0);
+    assert_eq!(zero.rotate(1.0), zero);
+}
+
+#[test]
+fn test_vector_products() {
+    let x = vector2::Vector2::new(2.0, 0.0);
+    let y = vector2::Vector2::new(0.0, 3.0);
+    assert_eq!(x.dot(&y), 0.0);
+    assert_eq!(x.cross(&y), 6.0);
+    assert_eq!(y.cross(&x), -6.0);
+
+    let parallel = vector2::Vector2::new(-4.0, 0.0);
+    assert_eq!(x.dot(&parallel), -8.0);
+    assert_eq!(x.cross(&parallel), 0.0);
+
+    assert_eq!(x.distance(&y), 13.0f64.sqrt());
+    assert_eq!(x.distance(&x), 0.0);
+}
+
+#[test]
+fn test_predictive_catch() {
+    fn frames_to_catch(predictive: bool) -> Option<u32> {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.players.0.pos = vector2::Vector2::new(-5.0, -2.0);
+        // Thrown by the right player, flying in a straight line across the left half
+        engine.frisbee.pos = vector2::Vector2::new(0.0, 3.0);
+        engine.frisbee.direction = vector2::Vector2::new(-1.0, -0.2).normalized();
+        engine.frisbee.speed = game_engine::INITIAL_FRISBEE_SPEED;
+        engine.frisbee.last_held = Some(player::PlayerSide::Right);
+
+        let mut agent = agent::PredictiveCatchAgent::new(120);
+        for frame in 1..200 {
+            let intent = if predictive {
+                agent::Agent::act(&mut agent, player::PlayerSide::Left, &mut engine)
+            } else {
+                agent::Intent::Move((engine.frisbee.pos - engine.players.0.pos).normalized())
+            };
+            engine.step((intent, agent::Intent::None));
+            if engine.frisbee.held_by_player == Some(player::PlayerSide::Left) {
+                return Some(frame);
+            }
+            if engine.state_of_game != game_engine::StateOfGame::Playing {
+                return None;
+            }
+        }
+        None
+    }
+
+    let predictive = frames_to_catch(true).expect("The predictive agent should catch the frisbee");
+    // Chasing the frisbee's current position lags behind it, here it never catches up
+    let naive = frames_to_catch(false).unwrap_or(u32::MAX);
+    assert!(predictive < naive);
+}
+
+#[test]
+fn test_goal_height() {
+    fn goals(field: game_engine::FieldConfig) -> u32 {
+        let throws = [
+            frisbee::ThrowDirection::Up,
+            frisbee::ThrowDirection::LightUp,
+            frisbee::ThrowDirection::Middle,
+            frisbee::ThrowDirection::LightDown,
+            frisbee::ThrowDirection::Down,
+        ];
+        let mut goals = 0;
+        for y in [-3.0, -1.5, 0.0, 1.5, 3.0].iter() {
+            for throw in throws.iter() {
+                let mut engine = game_engine::GameEngine::with_config(field);
+                engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+                engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+                engine.reset();
+                engine.state_of_game = game_engine::StateOfGame::Playing;
+                engine.players.0.pos = vector2::Vector2::new(-5.0, *y);
+                engine.frisbee.pos = engine.players.0.pos;
+                engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+                // Out of the way of every throw
+                engine.players.1.pos = vector2::Vector2::new(0.75, 4.4);
+
+                engine.step((agent::Intent::Throw(*throw), agent::Intent::None));
+                for _ in 0..300 {
+                    engine.step((agent::Intent::None, agent::Intent::None));
+                    if engine.players.0.score > 0 {
+                        goals += 1;
+                        break;
+                    }
+                }
+            }
+        }
+        goals
+    }
+
+    let wide = goals(game_engine::FieldConfig::default());
+    let narrow = goals(game_engine::FieldConfig::new(19.8, 8.8, 2.0));
+    assert!(wide > 0);
+    assert!(narrow < wide);
+}
+
+#[test]
+fn test_run_matches() {
+    let stats = sim::run_matches(agent::AgentType::Random, agent::AgentType::Random, 100, 600, 21);
+    assert_eq!(stats.left_wins + stats.right_wins + stats.draws, 100);
+    assert!(stats.average_frames > 0.0 && stats.average_frames <= 600.0);
+    assert_eq!(sim::run_matches(agent::AgentType::Random, agent::AgentType::Random, 100, 600, 21), stats);
+}
+
+#[test]
+fn test_match_iter() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::Random as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.reset();
+    engine.seed_rng(4);
+    let mut manual = 0;
+    loop {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+        manual += 1;
+        if engine.is_over() {
+            break;
+        }
+    }
+    let end_state = engine.state_of_game;
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    engine.seed_rng(4);
+    let frames = sim::MatchIter::new(engine, Box::new(agent::RandomAgent::new()), Box::new(agent::RandomAgent::new()));
+    let states: Vec<_> = frames.collect();
+    assert_eq!(states.len(), manual);
+    assert_eq!(states.last().unwrap().state_of_game, game_engine::state_to_i8(&end_state));
+}
+
+#[test]
+fn test_max_frames_draw() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.max_frames = Some(500);
+
+    // Nobody moves, the frisbee is served and caught but never thrown
+    for frame in 1..=500 {
+        assert!(engine.state_of_game != game_engine::StateOfGame::Draw);
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+        assert_eq!(engine.frame, frame);
+    }
+    assert!(engine.state_of_game == game_engine::StateOfGame::Draw);
+    assert!(engine.is_over());
+
+    engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    assert_eq!(engine.frame, 500);
+    assert_eq!(engine.players.0.score + engine.players.1.score, 0);
+}
+
+#[test]
+fn test_game_events() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+    engine.players.1.pos = vector2::Vector2::new(9.0, 3.0);
+
+    let throw = agent::Intent::Throw(frisbee::ThrowDirection::Middle);
+    engine.step((throw, agent::Intent::None));
+    while engine.state_of_game == game_engine::StateOfGame::Playing {
+        engine.step((agent::Intent::None, agent::Intent::None));
+    }
+
+    assert_eq!(engine.take_events(), vec![
+        game_engine::GameEvent::Thrown(player::PlayerSide::Left, frisbee::ThrowDirection::Middle),
+        game_engine::GameEvent::Scored(player::PlayerSide::Left),
+    ]);
+    assert!(engine.take_events().is_empty());
+}
+
+#[test]
+fn test_evaluate_state() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.pos = engine.players.0.pos;
+
+    let free = agent::evaluate_state(&engine, player::PlayerSide::Left);
+    let opponent_free = agent::evaluate_state(&engine, player::PlayerSide::Right);
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+    assert!(agent::evaluate_state(&engine, player::PlayerSide::Left) > free);
+    // And the other way around for the opponent
+    assert!(agent::evaluate_state(&engine, player::PlayerSide::Right) < opponent_free);
+}
+
+#[test]
+fn test_q_table_lookup() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    assert_eq!(engine.greedy_action(player::PlayerSide::Left), 0);
+    assert_eq!(engine.q_value_for(player::PlayerSide::Left, 3), 0.0);
+
+    let mut left = [0.0; agent::QVALUES_ACTIONS];
+    left[3] = 0.5;
+    left[11] = 2.0;
+    let mut right = [-1.0; agent::QVALUES_ACTIONS];
+    right[7] = 1.0;
+    engine.q_values.insert(engine.hash(), (left, right));
+    engine.q_values.insert(engine.hash() + 1, ([0.0; agent::QVALUES_ACTIONS], [0.0; agent::QVALUES_ACTIONS]));
+
+    assert_eq!(engine.greedy_action(player::PlayerSide::Left), 11);
+    assert_eq!(engine.greedy_action(player::PlayerSide::Right), 7);
+    assert_eq!(engine.q_value_for(player::PlayerSide::Left, 3), 0.5);
+    assert_eq!(engine.q_table_nonzero_count(), 1);
+}
+
+#[test]
+fn test_lazy_q_values() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::TabularQLearning as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.q_values = agent::get_blank_q_values();
+    assert!(engine.q_values.is_empty());
+
+    engine.reset();
+    engine.seed_rng(2);
+    let mut previous = engine.hash();
+    let mut visited = ::std::collections::HashSet::new();
+    for _ in 0..300 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+        let state = engine.hash();
+        // Same update as the trainer, the action doesn't matter for the table size
+        engine.update_q_value(player::PlayerSide::Left, previous, 0, -1.0, state);
+        visited.insert(previous);
+        previous = state;
+    }
+
+    // Only the updated states are stored
+    assert_eq!(engine.q_values.len(), visited.len());
+    assert!(engine.q_values.len() < 300);
+}
+
+#[test]
+fn test_double_q_update() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::DoubleQLearning as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.q_values.clear();
+    engine.seed_rng(1);
+
+    for i in 0..20 {
+        let before = (engine.q_values.clone(), engine.q_values_b.clone());
+        let updated_a = engine.update_double_q_value(player::PlayerSide::Left, i, 4, 1.0, i + 1);
+        let (table, other, before_table, before_other) = if updated_a {
+            (&engine.q_values, &engine.q_values_b, &before.0, &before.1)
+        } else {
+            (&engine.q_values_b, &engine.q_values, &before.1, &before.0)
+        };
+        assert_eq!(other, before_other);
+        assert!(table != before_table);
+        assert_eq!(table[&i].0[4], engine.learning_rate);
+    }
+    // Both tables end up being trained
+    assert!(!engine.q_values.is_empty() && !engine.q_values_b.is_empty());
+}
+
+#[test]
+fn test_reset_episode() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::TabularQLearning as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.q_values.clear();
+    engine.q_values.insert(12, ([0.25; agent::QVALUES_ACTIONS], [0.5; agent::QVALUES_ACTIONS]));
+    engine.reset();
+    engine.explo_rate = 0.3;
+    engine.players.0.score = 5;
+    engine.players.1.score = 3;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    let q_values = engine.q_values.clone();
+    let episode = engine.episode;
+    engine.reset_episode();
+
+    assert_eq!(engine.q_values, q_values);
+    assert_eq!(engine.explo_rate, 0.3);
+    assert_eq!(engine.episode, episode + 1);
+    assert_eq!(engine.players.0.score, 0);
+    assert_eq!(engine.players.1.score, 0);
+    assert!(engine.frisbee.held_by_player.is_none());
+    assert_eq!(engine.frisbee.pos, vector2::Vector2::zero());
+    assert!(engine.state_of_game == game_engine::StateOfGame::Playing);
+}
+
+#[test]
+fn test_player_config() {
+    fn frames_to_frisbee(config: player::PlayerConfig) -> u32 {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.set_player_config(player::PlayerSide::Left, config);
+        engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+        engine.frisbee.pos = vector2::Vector2::zero();
+
+        let mut frames = 0;
+        while engine.frisbee.held_by_player.is_none() {
+            engine.step((agent::Intent::Move(vector2::Vector2::new(1.0, 0.0)), agent::Intent::None));
+            frames += 1;
+            assert!(frames < 1000);
+        }
+        frames
+    }
+
+    let default = player::PlayerConfig::default();
+    let fast = player::PlayerConfig { move_speed: 2.0, ..default };
+    assert!(frames_to_frisbee(fast) < frames_to_frisbee(default));
+
+    // A dash stops after its duration, then cannot be repeated before the cooldown
+    let mut p = player::Player::new();
+    p.config = player::PlayerConfig { dash_duration_frames: 2, dash_cooldown_frames: 3, ..default };
+    p.dash(vector2::Vector2::new(2.5, 0.0));
+    p.update_slide(false);
+    assert!(p.slide.is_some());
+    p.update_slide(false);
+    assert!(p.slide.is_none());
+    assert!((p.pos.x - 0.8).abs() < 1e-9);
+    assert_eq!(p.dash_cooldown, 3);
+    assert!(!p.can_dash());
+}
+
+#[test]
+fn test_curved_throw() {
+    fn landing_point(dir: frisbee::ThrowDirection) -> vector2::Vector2 {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+        engine.players.1.pos = vector2::Vector2::new(9.0, 4.0);
+        engine.frisbee.pos = engine.players.0.pos;
+        engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+        engine.step((agent::Intent::Throw(dir), agent::Intent::None));
+        while engine.frisbee.pos.x < 8.0 {
+            engine.step((agent::Intent::None, agent::Intent::None));
+        }
+        engine.frisbee.pos
+    }
+
+    let straight = landing_point(frisbee::ThrowDirection::Middle);
+    let base = vector2::Vector2::new(1.0, 0.0);
+    let curved = landing_point(frisbee::ThrowDirection::Curved { base, curve: game_engine::CURVED_THROW_CURVE });
+    assert!(straight.y.abs() < 1e-9);
+    // Thrown toward +x, a positive curve bends up
+    assert!(curved.y > straight.y + 0.5);
+    assert!(curved.y < 4.4);
+}
+
+#[test]
+fn test_swept_wall_bounce() {
+    let launches = [
+        // Steep enough to cross both side walls in one step
+        (vector2::Vector2::new(0.05, 1.0), 100.0),
+        (vector2::Vector2::new(-0.3, -1.0), 60.0),
+        // Straight into the corner
+        (vector2::Vector2::new(9.9, 4.4), 30.0),
+    ];
+    for &(dir, speed) in launches.iter() {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.field.goal_height = 2.0;
+        engine.players.0.pos = vector2::Vector2::new(-9.0, -4.0);
+        engine.players.1.pos = vector2::Vector2::new(9.0, -4.0);
+        engine.frisbee.pos = vector2::Vector2::zero();
+        engine.frisbee.direction = dir.normalized();
+        engine.frisbee.speed = speed;
+        engine.frisbee.last_held = Some(player::PlayerSide::Left);
+
+        for _ in 0..300 {
+            engine.step((agent::Intent::None, agent::Intent::None));
+            if engine.state_of_game != game_engine::StateOfGame::Playing {
+                break;
+            }
+            assert!(engine.frisbee.pos.y.abs() <= engine.field.side_wall(), "{:?}", engine.frisbee.pos);
+            assert!(engine.frisbee.pos.x.abs() <= engine.field.goal_line(), "{:?}", engine.frisbee.pos);
+        }
+    }
+}
+
+#[test]
+fn test_make_agent() {
+    assert_eq!(agent::make_agent(agent::AgentType::Dijkstra).get_type(), agent::AgentType::Dijkstra);
+    for i in 0..agent::AgentType::None as i8 {
+        let agent_type = agent::agent_type_from_i8(i);
+        let params = agent::AgentParams { frames: 10.0, sim: 2, time_budget: ::std::time::Duration::from_millis(1) };
+        assert_eq!(agent::make_agent_with(agent_type, params).get_type(), agent_type);
+    }
+}
+
+#[test]
+fn test_rollouts_are_seeded() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::RandomRollout as i8, 30.0, 2);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-2.0, 0.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    let agent = agent::RandomRolloutAgent { commit_frames: 1, playout_frames: 30.0, sim: 2, throws: frisbee::THROW_DIRECTIONS.to_vec(), curved_throws: true, opponent_policy: agent::AgentType::Random };
+    let serial = agent.search_serial(player::PlayerSide::Left, &engine, 7);
+    assert_eq!(format!("{:?}", serial), format!("{:?}", agent.search_serial(player::PlayerSide::Left, &engine, 7)));
+    #[cfg(feature = "parallel")]
+    assert_eq!(format!("{:?}", serial), format!("{:?}", agent.search_parallel(player::PlayerSide::Left, &engine, 7)));
+}
+
+#[test]
+fn test_timed_rollout() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-2.0, 0.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    let budget = ::std::time::Duration::from_millis(30);
+    let agent = agent::TimedRolloutAgent::new(30.0, budget);
+    let start = ::std::time::Instant::now();
+    let (_, count) = agent.search(player::PlayerSide::Left, &engine, 3);
+    let elapsed = start.elapsed();
+    assert!(count > 0);
+    // The search always uses its whole budget; the upper bound is left
+    // loose since a loaded machine can stall any single rollout
+    assert!(elapsed >= budget);
+    assert!(elapsed < budget * 100);
+}
+
+#[test]
+fn test_canonical_hash() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    engine.explo_rate = 0.0;
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 2.0);
+    engine.players.1.pos = vector2::Vector2::new(7.0, -1.0);
+    engine.frisbee.pos = vector2::Vector2::new(-3.0, 1.0);
+    engine.frisbee.direction = vector2::Vector2::new(1.0, 0.3).normalized();
+    engine.frisbee.last_held = Some(player::PlayerSide::Left);
+
+    let mut mirror = game_engine::GameEngine::new();
+    mirror.reset();
+    mirror.explo_rate = 0.0;
+    mirror.players.0.pos = vector2::Vector2::new(-7.0, -1.0);
+    mirror.players.1.pos = vector2::Vector2::new(5.0, 2.0);
+    mirror.frisbee.pos = vector2::Vector2::new(3.0, 1.0);
+    mirror.frisbee.direction = vector2::Vector2::new(-1.0, 0.3).normalized();
+    mirror.frisbee.last_held = Some(player::PlayerSide::Right);
+
+    assert_eq!(engine.hash_canonical(player::PlayerSide::Left), engine.hash());
+    assert_eq!(engine.hash_canonical(player::PlayerSide::Left), mirror.hash_canonical(player::PlayerSide::Right));
+    assert!(mirror.hash() != mirror.hash_canonical(player::PlayerSide::Right));
+
+    // Learned on the left, moving right becomes moving left on the right side
+    let mut values = [0.0; agent::QVALUES_ACTIONS];
+    values[agent::human_intent_to_index(agent::HumanIntent::RIGHT) as usize] = 1.0;
+    mirror.q_values.insert(engine.hash(), (values, [0.0; agent::QVALUES_ACTIONS]));
+    let mut q_agent = agent::TabularQLearningAgent { canonical: true, ..agent::TabularQLearningAgent::new() };
+    match mirror.ask_agent(&mut q_agent, player::PlayerSide::Right) {
+        agent::Intent::Move(dir) => assert_eq!(dir, vector2::Vector2::new(-1.0, 0.0)),
+        intent => panic!("Unexpected intent {:?}", intent),
+    };
+}
+
+#[test]
+fn test_hash_range() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    let sides = [None, Some(player::PlayerSide::Left), Some(player::PlayerSide::Right)];
+    let directions = [
+        vector2::Vector2::new(1.0, 0.0),
+        vector2::Vector2::new(-1.0, 1.0).normalized(),
+        vector2::Vector2::new(0.3, -1.0).normalized(),
+        vector2::Vector2::zero(),
+    ];
+
+    // Steps a little past the field in every direction
+    let mut x: f64 = -10.5;
+    while x <= 10.5 {
+        let mut y = -5.5;
+        while y <= 5.5 {
+            engine.players.0.pos = vector2::Vector2::new(x.min(-0.75), y);
+            engine.players.1.pos = vector2::Vector2::new((-x).max(0.75), -y);
+            engine.frisbee.pos = vector2::Vector2::new(-x, y);
+            for last_held in sides.iter() {
+                engine.frisbee.last_held = *last_held;
+                for direction in directions.iter() {
+                    engine.frisbee.direction = *direction;
+                    assert!(engine.hash() < agent::QVALUES_MAX_STATES);
+                    assert!(engine.hash_canonical(player::PlayerSide::Right) < agent::QVALUES_MAX_STATES);
+                }
+            }
+            y += 0.25;
+        }
+        x += 0.25;
+    }
+}
+
+#[test]
+fn test_n_step_returns() {
+    let mut q_values = agent::get_blank_q_values();
+    let mut buffer = agent::NStepBuffer::new(3);
+    let (learning_rate, discount_factor) = (0.5, 0.9);
+
+    // Only the last step is rewarded, then the episode ends
+    buffer.push(&mut q_values, player::PlayerSide::Left, 1, 2, 0.0, 2, learning_rate, discount_factor);
+    buffer.push(&mut q_values, player::PlayerSide::Left, 2, 2, 0.0, 3, learning_rate, discount_factor);
+    assert!(q_values.is_empty());
+    buffer.push(&mut q_values, player::PlayerSide::Left, 3, 2, 1.0, 4, learning_rate, discount_factor);
+    buffer.flush(&mut q_values, player::PlayerSide::Left, learning_rate, discount_factor);
+    assert!(buffer.transitions.is_empty());
+
+    for (state, steps_before_reward) in [(1, 2), (2, 1), (3, 0)].iter() {
+        let expected = learning_rate * discount_factor.powi(*steps_before_reward);
+        assert!((q_values[state].0[2] - expected).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_experience_replay() {
+    fn learned_value(replays: usize) -> f32 {
+        let mut engine = game_engine::GameEngine::new();
+        engine.seed_rng(3);
+        engine.learning_rate = 0.1;
+        engine.set_experience_capacity(4);
+        // The next state is never visited, the target is the reward alone
+        engine.update_q_value(player::PlayerSide::Left, 1, 3, 1.0, 2);
+        assert_eq!(engine.replay_sample_and_update(replays), replays);
+        engine.q_values[&1].0[3]
+    }
+
+    let single = learned_value(0);
+    let replayed = learned_value(10);
+    assert!((1.0 - replayed).abs() < (1.0 - single).abs());
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.set_experience_capacity(4);
+    for state in 0..10 {
+        engine.update_q_value(player::PlayerSide::Right, state, 0, 0.0, state + 1);
+    }
+    // Only the most recent transitions are kept
+    assert_eq!(engine.experience.len(), 4);
+    assert_eq!(engine.experience[0].state, 6);
+    engine.set_experience_capacity(0);
+    assert!(engine.experience.is_empty());
+    assert_eq!(engine.replay_sample_and_update(5), 0);
+}
+
+#[test]
+fn test_observation() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::Random as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.seed_rng(5);
+    engine.reset();
+    let config = player::PlayerConfig { dash_cooldown_frames: 20, ..player::PlayerConfig::default() };
+    engine.set_player_config(player::PlayerSide::Left, config);
+
+    for _ in 0..2000 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+        for side in [player::PlayerSide::Left, player::PlayerSide::Right].iter() {
+            let observation = engine.observation(*side);
+            assert_eq!(observation.len(), game_engine::OBSERVATION_SIZE);
+            assert!(observation.iter().all(|v| (-1.0..=1.0).contains(v)), "{:?}", observation);
+        }
+        if engine.is_over() {
+            engine.reset();
+        }
+    }
+
+    // Each side sees itself first
+    let left = engine.observation(player::PlayerSide::Left);
+    let right = engine.observation(player::PlayerSide::Right);
+    assert_eq!(left[0..2], right[2..4]);
+    assert_eq!(left[8], -right[8]);
+}
+
+#[test]
+fn test_apply_action() {
+    let throw_up = agent::human_intent_to_index(agent::HumanIntent::THROW | agent::HumanIntent::UP);
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    engine.apply_action(player::PlayerSide::Left, throw_up);
+    assert_eq!(engine.inputs.0, agent::HumanIntent::THROW | agent::HumanIntent::UP);
+    let inputs = engine.inputs;
+    engine.epoch(inputs.0, inputs.1);
+    assert!(engine.frisbee.held_by_player.is_none());
+    assert!(engine.frisbee.direction.y > 0.0 && engine.frisbee.direction.x > 0.0);
+
+    // Same throw without going through the agents
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+    engine.frisbee.direction = vector2::Vector2::zero();
+    engine.step_with_actions(throw_up, 0);
+    assert!(engine.frisbee.direction.y > 0.0);
+    assert_eq!(engine.inputs.1, agent::HumanIntent::IDLE);
+}
+
+#[test]
+fn test_reward_fn() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.agents = (
+        Some(Box::new(agent::TabularQLearningAgent::with_n_step(1))),
+        Some(Box::new(agent::RandomAgent::new())),
+    );
+    engine.seed_rng(2);
+    engine.reset();
+    engine.explo_rate = 1.0;
+    engine.learning_rate = 1.0;
+    engine.discount_factor = 0.0;
+    engine.reward_fn = Some(Box::new(|_, _| 0.5));
+
+    for _ in 0..300 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert_eq!(engine.rewards, (0.5, 0.5));
+    // Whatever happened, every update used the same reward
+    assert!(engine.q_table_nonzero_count() > 0);
+    for (left, _) in engine.q_values.values() {
+        assert!(left.iter().all(|&v| v == 0.0 || v == 0.5));
+    }
+}
+
+#[test]
+fn test_end_episode_flushes() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.agents = (
+        Some(Box::new(agent::TabularQLearningAgent::with_n_step(3))),
+        Some(Box::new(agent::RandomAgent::new())),
+    );
+    engine.seed_rng(2);
+    engine.reset_episode();
+    engine.explo_rate = 1.0;
+    engine.learning_rate = 1.0;
+    engine.discount_factor = 0.0;
+    engine.reward_fn = Some(Box::new(|_, _| 1.0));
+
+    // Fewer transitions than the agent looks ahead, none of them is credited yet
+    for _ in 0..2 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert_eq!(engine.q_table_nonzero_count(), 0);
+
+    // Ending the episode credits them with the rewards they got
+    engine.reset_episode();
+    assert!(engine.q_table_nonzero_count() > 0);
+    let count = engine.q_table_nonzero_count();
+    engine.reset();
+    assert_eq!(engine.q_table_nonzero_count(), count);
+
+    // A curriculum ends the episode of the stage that played it
+    engine.q_values.clear();
+    engine.agents.0 = Some(Box::new(agent::DifficultyAgent::new(vec![(0, Box::new(agent::TabularQLearningAgent::with_n_step(3)))])));
+    engine.reset_episode();
+    for _ in 0..2 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert_eq!(engine.q_table_nonzero_count(), 0);
+    engine.reset_episode();
+    assert!(engine.q_table_nonzero_count() > 0);
+}
+
+#[test]
+fn test_frisbee_velocity() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+    engine.frisbee.speed = game_engine::INITIAL_FRISBEE_SPEED;
+    assert_eq!(engine.frisbee.velocity(), vector2::Vector2::zero());
+
+    engine.step((agent::Intent::Throw(frisbee::ThrowDirection::LightUp), agent::Intent::None));
+    let velocity = engine.frisbee.velocity();
+    assert!(velocity.length() > 0.0);
+
+    let before = engine.frisbee.pos;
+    engine.step((agent::Intent::None, agent::Intent::None));
+    assert!(((engine.frisbee.pos - before) - velocity).length() < 1e-9);
+
+    // Copies see the same movement
+    let mut copy = game_engine::GameEngine::new();
+    engine.copy_in(&mut copy);
+    assert_eq!(copy.frisbee.velocity(), engine.frisbee.velocity());
+}
+
+#[test]
+fn test_frisbee_drag() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.set_frisbee_config(game_engine::FrisbeeConfig { drag: 0.01, ..game_engine::FrisbeeConfig::default() });
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+    engine.players.1.pos = vector2::Vector2::new(9.0, 4.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    engine.step((agent::Intent::Throw(frisbee::ThrowDirection::Up), agent::Intent::None));
+    let mut speed = engine.frisbee.speed;
+    for _ in 0..30 {
+        engine.step((agent::Intent::None, agent::Intent::None));
+        assert!(engine.frisbee.speed < speed);
+        speed = engine.frisbee.speed;
+    }
+}
+
+#[test]
+fn test_frisbee_max_speed() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    // Slower than a throw
+    engine.set_frisbee_config(game_engine::FrisbeeConfig { drag: 0.0, max_speed: 1.5 });
+    engine.players.0.pos = vector2::Vector2::new(-5.0, 0.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    engine.step((agent::Intent::Throw(frisbee::ThrowDirection::Middle), agent::Intent::None));
+    assert_eq!(engine.frisbee.speed, 1.5);
+}
+
+#[test]
+fn test_stalemate() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.stalemate = Some(game_engine::StalemateConfig { window: 10, repeats: 3 });
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Right);
+
+    // The left player goes back and forth between two cells
+    let positions = [vector2::Vector2::new(-5.0, 0.0), vector2::Vector2::new(-3.0, 2.0)];
+    for frame in 0..7 {
+        assert!(!engine.is_stalemate());
+        assert!(engine.state_of_game == game_engine::StateOfGame::Playing);
+        engine.players.0.pos = positions[frame % 2];
+        engine.step((agent::Intent::None, agent::Intent::None));
+    }
+    // The first position came back a fourth time
+    assert!(engine.is_stalemate());
+    assert!(engine.state_of_game == game_engine::StateOfGame::Draw);
+
+    // The left player waits for the frisbee at rest while the right one walks to it, spending 10 frames in each cell
+    engine.reset();
+    engine.stalemate = Some(game_engine::StalemateConfig { window: 30, repeats: 15 });
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(9.0, 0.0));
+    engine.set_frisbee(vector2::Vector2::new(2.0, 0.0), vector2::Vector2::zero(), None);
+    engine.frisbee.last_held = Some(player::PlayerSide::Left);
+    for _ in 0..30 {
+        engine.step((agent::Intent::None, agent::Intent::Move(vector2::Vector2::new(-1.0, 0.0))));
+    }
+    assert!(engine.state_of_game == game_engine::StateOfGame::Playing);
+}
+
+#[test]
+fn test_won_state() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.score = game_engine::MAX_ROUND_POINTS - 1;
+
+    // Left throws straight into the right goal
+    engine.frisbee.held_by_player = None;
+    engine.frisbee.last_held = Some(player::PlayerSide::Left);
+    engine.frisbee.pos = vector2::Vector2::new(engine.field.width / 2.0 - 0.2, 0.0);
+    engine.frisbee.launch(vector2::Vector2::new(1.0, 0.0), game_engine::INITIAL_FRISBEE_SPEED);
+    engine.players.1.pos = vector2::Vector2::new(engine.field.width / 2.0 - 0.5, engine.field.height / 2.0 - 0.1);
+    for _ in 0..10 {
+        engine.step((agent::Intent::None, agent::Intent::None));
+    }
+    assert!(engine.players.0.score >= game_engine::MAX_ROUND_POINTS);
+    assert!(engine.state_of_game == game_engine::StateOfGame::Won(player::PlayerSide::Left));
+    assert!(engine.is_over());
+}
+
+#[test]
+fn test_eval_mode() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.agents = (
+        Some(Box::new(agent::TabularQLearningAgent::with_n_step(2))),
+        Some(Box::new(agent::SarsaAgent::new())),
+    );
+    engine.seed_rng(8);
+    engine.reset();
+    engine.explo_rate = 1.0;
+    for _ in 0..200 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert!(engine.q_table_nonzero_count() > 0);
+
+    let before = engine.q_values.clone();
+    engine.eval_mode = true;
+    engine.reset();
+    while !engine.is_over() {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert!(engine.q_values == before);
+}
+
+#[test]
+fn test_throw_set() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    let only_middle = vec![frisbee::ThrowDirection::Middle];
+    let mut rollout = agent::RandomRolloutAgent { commit_frames: 1, playout_frames: 30.0, sim: 2, throws: only_middle.clone(), curved_throws: false, opponent_policy: agent::AgentType::Random };
+    let mut dijkstra = agent::DijkstraAgent { throws: only_middle, ..agent::DijkstraAgent::new(2) };
+    for i in 0..5 {
+        engine.seed_rng(i);
+        engine.players.0.pos = vector2::Vector2::new(-8.0 + 4.0 * i as f64, 3.0 - 1.5 * i as f64);
+        engine.frisbee.pos = engine.players.0.pos;
+        let intents = [
+            engine.ask_agent(&mut rollout, player::PlayerSide::Left),
+            engine.ask_agent(&mut dijkstra, player::PlayerSide::Left),
+        ];
+        // Holding on to the frisbee is fine, any throw has to be one of the set
+        for intent in intents.iter() {
+            match intent {
+                agent::Intent::Throw(frisbee::ThrowDirection::Middle) | agent::Intent::None | agent::Intent::Move(_) | agent::Intent::Dash(_) => {},
+                intent => panic!("Unexpected intent {:?}", intent),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dijkstra_tie_break() {
+    use rand::SeedableRng;
+
+    let node = |dir: vector2::Vector2| {
+        agent::Node { engine: game_engine::GameEngine::new(), first_intent: agent::Intent::Move(dir), cost: 2, score: 5 }
+    };
+    let up = vector2::Vector2::new(0.0, 1.0);
+    let best = vec![node(up), node(vector2::Vector2::new(0.0, -1.0))];
+
+    let mut ups = 0;
+    for seed in 0..1000 {
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(seed);
+        if agent::pick_cheapest(&best, &mut rng) == agent::Intent::Move(up) {
+            ups += 1;
+        }
+    }
+    assert!(ups > 400 && ups < 600, "{} ups out of 1000", ups);
+
+    // A cheaper node always wins
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+    let best = vec![node(up), agent::Node { cost: 1, ..node(vector2::Vector2::new(1.0, 0.0)) }];
+    assert_eq!(agent::pick_cheapest(&best, &mut rng), agent::Intent::Move(vector2::Vector2::new(1.0, 0.0)));
+}
+
+#[test]
+fn test_dijkstra_verbose() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+
+    let mut dijkstra = agent::DijkstraAgent::new(2);
+    let best = agent::get_best(&dijkstra.search(player::PlayerSide::Left, &engine));
+    assert!(!best.is_empty());
+    // Quiet by default, `act` keeps nothing
+    assert!(dijkstra.diagnostics(&best).is_none());
+
+    agent::Agent::act(&mut dijkstra, player::PlayerSide::Left, &mut engine);
+    assert!(dijkstra.diagnostics_log.is_none());
+
+    dijkstra.verbose = true;
+    let diagnostics = dijkstra.diagnostics(&best).unwrap();
+    assert_eq!(diagnostics.lines().count(), best.len());
+    // Left for the caller to log rather than printed
+    agent::Agent::act(&mut dijkstra, player::PlayerSide::Left, &mut engine);
+    assert!(dijkstra.diagnostics_log.as_ref().is_some_and(|log| !log.is_empty()));
+}
+
+#[test]
+fn test_rollout_opponent_policy() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    // The left player is about to throw at a goal the right player can still defend
+    engine.players.0.pos = vector2::Vector2::new(4.0, 0.0);
+    engine.players.1.pos = vector2::Vector2::new(8.0, 2.0);
+    engine.frisbee.pos = engine.players.0.pos;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    let idle = agent::RandomRolloutAgent {
+        commit_frames: 1,
+        playout_frames: 60.0,
+        sim: 4,
+        throws: frisbee::THROW_DIRECTIONS.to_vec(),
+        curved_throws: false,
+        opponent_policy: agent::AgentType::HumanPlayer,
+    };
+    let catching = agent::RandomRolloutAgent { opponent_policy: agent::AgentType::PredictiveCatch, ..idle.clone() };
+    let idle_scores = idle.rollout_scores(player::PlayerSide::Left, &engine, 3);
+    let catching_scores = catching.rollout_scores(player::PlayerSide::Left, &engine, 3);
+    assert_eq!(idle_scores.len(), catching_scores.len());
+    assert!(idle_scores != catching_scores);
+    // An opponent going for the frisbee can only stop goals
+    assert!(catching_scores.iter().map(|&s| s as i32).sum::<i32>() < idle_scores.iter().map(|&s| s as i32).sum::<i32>());
+
+    // An opponent with its own generator is reseeded for every rollout, the scores only depend on the seed
+    let mut mcts = agent::RandomRolloutAgent { playout_frames: 10.0, sim: 1, ..idle.clone() };
+    mcts.set_opponent_policy(agent::AgentType::MCTS).unwrap();
+    assert_eq!(mcts.rollout_scores(player::PlayerSide::Left, &engine, 3), mcts.rollout_scores(player::PlayerSide::Left, &engine, 3));
+    assert!(mcts.set_opponent_policy(agent::AgentType::None).is_err());
+    assert_eq!(mcts.opponent_policy, agent::AgentType::MCTS);
+}
+
+#[test]
+fn test_intent_eq_display() {
+    let diagonal = vector2::Vector2::new(1.0, 1.0).normalized();
+    assert_eq!(agent::Intent::Move(diagonal), agent::Intent::Move(vector2::Vector2::new(1.0, 1.0).normalized()));
+    assert_ne!(agent::Intent::Move(diagonal), agent::Intent::Dash(diagonal));
+    assert_ne!(agent::Intent::Throw(frisbee::ThrowDirection::Up), agent::Intent::Throw(frisbee::ThrowDirection::Down));
+
+    assert_eq!(agent::Intent::Move(diagonal).to_string(), "Move(0.71, 0.71)");
+    assert_eq!(agent::Intent::Throw(frisbee::ThrowDirection::Up).to_string(), "Throw(Up)");
+    assert_eq!(agent::Intent::None.to_string(), "None");
+}
+
+#[test]
+fn test_vector_approx_eq() {
+    let a = vector2::Vector2::new(0.3, -2.0);
+    let b = vector2::Vector2::new(0.3 + 1e-7, -2.0 - 1e-7);
+    assert!(a != b);
+    assert!(a.approx_eq(&b, 1e-6));
+    assert!(!a.approx_eq(&b, 1e-8));
+    assert!(!a.approx_eq(&vector2::Vector2::new(0.3, -1.9), 1e-6));
+
+    // 0.1 + 0.2 isn't exactly 0.3
+    let sum = vector2::Vector2::new(0.1, 0.0) + vector2::Vector2::new(0.2, 0.0);
+    assert!(sum != vector2::Vector2::new(0.3, 0.0));
+    assert!(sum.approx_eq(&vector2::Vector2::new(0.3, 0.0), 1e-9));
+}
+
+#[test]
+fn test_dijkstra_no_progress() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-6.0, 0.0);
+    engine.players.1.pos = vector2::Vector2::new(8.0, 3.0);
+    engine.frisbee.held_by_player = None;
+    engine.frisbee.speed = 0.0;
+    engine.frisbee.pos = vector2::Vector2::new(-1.0, 0.0);
+
+    let distance = |engine: &game_engine::GameEngine| engine.frisbee.pos.distance(&engine.players.0.pos);
+    let weights = agent::DijkstraWeights::default();
+
+    // Moving perpendicular to the frisbee barely changes the distance
+    let before = distance(&engine);
+    engine.step((agent::Intent::Move(vector2::Vector2::new(0.0, 1.0)), agent::Intent::None));
+    let after = distance(&engine);
+    assert!(after != before);
+    assert_eq!(weights.step_score(before, after, false), -weights.no_progress_penalty);
+
+    // Moving toward it is progress, and away from it regress
+    let before = distance(&engine);
+    engine.step((agent::Intent::Move(vector2::Vector2::new(1.0, 0.0)), agent::Intent::None));
+    let after = distance(&engine);
+    assert_eq!(weights.step_score(before, after, false), weights.progress_bonus);
+    assert_eq!(weights.step_score(after, before, false), -weights.regress_penalty);
+    assert_eq!(weights.step_score(before, after, true), weights.possession_bonus);
+}
+
+#[test]
+fn test_set_human_input() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Right);
+
+    let y = engine.players.0.pos.y;
+    engine.set_human_input(player::PlayerSide::Left, agent::HumanIntent::UP);
+    engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    assert!(engine.players.0.pos.y > y);
+
+    // Only used for one epoch
+    let y = engine.players.0.pos.y;
+    engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    assert_eq!(engine.players.0.pos.y, y);
+}
+
+#[test]
+fn test_analog_throw() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+
+    let at = |degrees: f64| {
+        let radians = degrees.to_radians();
+        vector2::Vector2::new(radians.cos(), radians.sin())
+    };
+    let throw = |engine: &game_engine::GameEngine, degrees: f64, side: player::PlayerSide| {
+        agent::human_intent_to_intent_analog(engine, at(degrees), true, side)
+    };
+    let left = player::PlayerSide::Left;
+    assert_eq!(throw(&engine, 10.0, left), agent::Intent::Throw(frisbee::ThrowDirection::Middle));
+    assert_eq!(throw(&engine, 30.0, left), agent::Intent::Throw(frisbee::ThrowDirection::LightUp));
+    assert_eq!(throw(&engine, -45.0, left), agent::Intent::Throw(frisbee::ThrowDirection::LightDown));
+    assert_eq!(throw(&engine, 80.0, left), agent::Intent::Throw(frisbee::ThrowDirection::Up));
+    assert_eq!(throw(&engine, -135.0, left), agent::Intent::Throw(frisbee::ThrowDirection::Down));
+    assert_eq!(throw(&engine, 175.0, left), agent::Intent::Throw(frisbee::ThrowDirection::Custom(vector2::Vector2::new(-1.0, 0.0))));
+
+    // The right player aims the other way
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Right);
+    let right = player::PlayerSide::Right;
+    assert_eq!(throw(&engine, 150.0, right), agent::Intent::Throw(frisbee::ThrowDirection::LightUp));
+    assert_eq!(throw(&engine, 30.0, right), agent::Intent::Throw(frisbee::ThrowDirection::Up));
+
+    // Sectors are tunable
+    engine.analog_throws.middle = 40f64.to_radians();
+    assert_eq!(throw(&engine, 150.0, right), agent::Intent::Throw(frisbee::ThrowDirection::Middle));
+
+    // Without the frisbee the stick moves or dashes
+    assert_eq!(agent::human_intent_to_intent_analog(&engine, at(90.0), false, left), agent::Intent::Move(at(90.0)));
+    assert_eq!(agent::human_intent_to_intent_analog(&engine, vector2::Vector2::zero(), false, left), agent::Intent::None);
+}
+
+#[test]
+fn test_analog_movement() {
+    fn walk(stick: vector2::Vector2) -> f64 {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.frisbee.held_by_player = Some(player::PlayerSide::Right);
+        engine.inputs_analog.0 = stick;
+
+        let start = engine.players.0.pos;
+        for _ in 0..10 {
+            // The stick is used over the bitflags
+            engine.epoch(agent::HumanIntent::DOWN, agent::HumanIntent::IDLE);
+        }
+        engine.players.0.pos.y - start.y
+    }
+
+    let full = walk(vector2::Vector2::new(0.0, 1.0));
+    assert!(full > 0.0);
+    assert!((walk(vector2::Vector2::new(0.0, 0.5)) - full / 2.0).abs() < 1e-9);
+    // Past the edge of the stick the speed is capped
+    assert!((walk(vector2::Vector2::new(0.0, 3.0)) - full).abs() < 1e-9);
+}
+
+#[test]
+fn test_seek_agent() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-6.0, 0.0);
+    engine.frisbee.held_by_player = None;
+    engine.frisbee.pos = vector2::Vector2::new(-5.0, 0.5);
+
+    let mut seek = agent::SeekAgent {};
+    match engine.ask_agent(&mut seek, player::PlayerSide::Left) {
+        agent::Intent::Move(dir) => assert!(dir.x > 0.0),
+        intent => panic!("Unexpected intent {:?}", intent),
+    };
+    // Far away it dashes
+    engine.frisbee.pos = vector2::Vector2::new(-1.0, 0.5);
+    match engine.ask_agent(&mut seek, player::PlayerSide::Left) {
+        agent::Intent::Dash(dir) => assert!(dir.x > 0.0),
+        intent => panic!("Unexpected intent {:?}", intent),
+    };
+
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Left);
+    assert_eq!(engine.ask_agent(&mut seek, player::PlayerSide::Left), agent::Intent::Throw(frisbee::ThrowDirection::Middle));
+    assert_eq!(agent::agent_type_from_i8(agent::AgentType::Seek as i8), agent::AgentType::Seek);
+}
+
+#[test]
+fn test_catch_radius() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-6.0, 0.0);
+    engine.frisbee.held_by_player = None;
+    engine.frisbee.last_held = Some(player::PlayerSide::Right);
+    engine.frisbee.speed = 0.0;
+    engine.frisbee.pos = vector2::Vector2::new(-4.5, 0.0);
+
+    assert!(!engine.can_catch(player::PlayerSide::Left));
+    engine.step((agent::Intent::None, agent::Intent::None));
+    assert!(engine.frisbee.held_by_player.is_none());
+
+    engine.catch_radius = 2.0;
+    assert!(engine.can_catch(player::PlayerSide::Left));
+    engine.step((agent::Intent::None, agent::Intent::None));
+    assert_eq!(engine.frisbee.held_by_player, Some(player::PlayerSide::Left));
+}
+
+#[test]
+fn test_dijkstra_weights() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    // The frisbee slides slowly toward the player, one step is enough to catch it
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-6.0, 0.0));
+    engine.set_frisbee(vector2::Vector2::new(-5.0, 0.0), vector2::Vector2::new(-0.1, 0.0), None);
+    engine.frisbee.last_held = Some(player::PlayerSide::Right);
+    let holds_after = |engine: &game_engine::GameEngine, intent: agent::Intent| {
+        let mut next = game_engine::GameEngine::new();
+        engine.copy_in(&mut next);
+        next.step((intent, agent::Intent::None));
+        next.frisbee.held_by_player == Some(player::PlayerSide::Left)
+    };
+
+    let mut holding = agent::DijkstraAgent::new(1);
+    assert_eq!(holding.weights, agent::DijkstraWeights::default());
+    let intent = engine.ask_agent(&mut holding, player::PlayerSide::Left);
+    assert!(holds_after(&engine, intent));
+
+    // Without the possession bonus, getting closer to the frisbee scores better than catching it
+    let mut careless = agent::DijkstraAgent::new(1);
+    careless.weights.possession_bonus = 0;
+    let intent = engine.ask_agent(&mut careless, player::PlayerSide::Left);
+    assert!(!holds_after(&engine, intent));
+}
+
+#[test]
+fn test_dijkstra_mid_dash() {
+    use rand::SeedableRng;
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Right);
+    engine.players.0.dash(vector2::Vector2::new(0.0, 2.0));
+    assert!(engine.players.0.slide.is_some());
+    assert!(engine.legal_intents(player::PlayerSide::Left).is_empty());
+
+    let mut dijkstra = agent::DijkstraAgent::new(3);
+    assert_eq!(engine.ask_agent(&mut dijkstra, player::PlayerSide::Left), agent::Intent::None);
+    // Nothing to pick from either
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+    assert_eq!(agent::pick_cheapest(&[], &mut rng), agent::Intent::None);
+}
+
+#[test]
+fn test_can_dash() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.held_by_player = Some(player::PlayerSide::Right);
+    engine.set_player_config(player::PlayerSide::Left, player::PlayerConfig { dash_cooldown_frames: 5, ..player::PlayerConfig::default() });
+
+    assert!(engine.can_dash(player::PlayerSide::Left));
+    engine.step((agent::Intent::Dash(vector2::Vector2::new(0.0, 1.0)), agent::Intent::None));
+    let mut frames = 0;
+    while !engine.can_dash(player::PlayerSide::Left) {
+        engine.step((agent::Intent::None, agent::Intent::None));
+        frames += 1;
+        assert!(frames < 100);
+    }
+    // The whole dash, then the cooldown
+    assert!(frames > 5);
+    assert!(engine.players.0.slide.is_none());
+
+    // A random agent never tries to dash meanwhile
+    engine.step((agent::Intent::Dash(vector2::Vector2::new(0.0, -1.0)), agent::Intent::None));
+    let mut random = agent::RandomAgent::new();
+    for _ in 0..200 {
+        assert!(!engine.can_dash(player::PlayerSide::Left));
+        if let agent::Intent::Dash(_) = engine.ask_agent(&mut random, player::PlayerSide::Left) {
+            panic!("Dashed on cooldown");
+        }
+    }
+}
+
+#[test]
+fn test_mirror() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.0.pos = vector2::Vector2::new(-4.0, 1.5);
+    engine.players.0.score = 3;
+    engine.players.1.pos = vector2::Vector2::new(7.0, -2.0);
+    engine.players.1.dash(vector2::Vector2::new(-1.0, 1.0));
+    engine.frisbee.held_by_player = None;
+    engine.frisbee.last_held = Some(player::PlayerSide::Left);
+    engine.frisbee.pos = vector2::Vector2::new(1.0, 2.0);
+    engine.frisbee.launch(vector2::Vector2::new(0.8, 0.6), game_engine::INITIAL_FRISBEE_SPEED);
+    engine.frisbee.curve = 0.25;
+    engine.inputs = (agent::HumanIntent::UP | agent::HumanIntent::RIGHT, agent::HumanIntent::LEFT);
+    engine.rewards = (1.0, -1.0);
+
+    let mirrored = engine.mirror();
+    assert_eq!(mirrored.players.0.pos, vector2::Vector2::new(-7.0, -2.0));
+    assert_eq!(mirrored.players.1.score, 3);
+    assert_eq!(mirrored.players.1.side, Some(player::PlayerSide::Right));
+    assert_eq!(mirrored.frisbee.pos, vector2::Vector2::new(-1.0, 2.0));
+    assert_eq!(mirrored.frisbee.last_held, Some(player::PlayerSide::Right));
+    assert_eq!(mirrored.inputs, (agent::HumanIntent::RIGHT, agent::HumanIntent::UP | agent::HumanIntent::LEFT));
+    assert_eq!(mirrored.hash_canonical(player::PlayerSide::Left), engine.hash_canonical(player::PlayerSide::Right));
+
+    let back = mirrored.mirror();
+    for (a, b) in [(&back.players.0, &engine.players.0), (&back.players.1, &engine.players.1)].iter() {
+        assert_eq!(a.pos, b.pos);
+        assert_eq!(a.side, b.side);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.slide.map(|s| (s.origin, s.target, s.dir)), b.slide.map(|s| (s.origin, s.target, s.dir)));
+    }
+    assert_eq!(back.frisbee.pos, engine.frisbee.pos);
+    assert_eq!(back.frisbee.direction, engine.frisbee.direction);
+    assert_eq!(back.frisbee.curve, engine.frisbee.curve);
+    assert_eq!(back.frisbee.last_held, engine.frisbee.last_held);
+    assert_eq!(back.inputs, engine.inputs);
+    assert_eq!(back.rewards, engine.rewards);
+    assert_eq!(back.hash(), engine.hash());
+}
+
+#[test]
+fn test_step_rl() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    // Waiting for the serve isn't an episode
+    let result = engine.step_rl(0, 0);
+    assert_eq!(result, game_engine::StepResult { reward_left: 0.0, reward_right: 0.0, done: false });
+
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.held_by_player = None;
+    engine.frisbee.last_held = Some(player::PlayerSide::Left);
+    engine.frisbee.pos = vector2::Vector2::new(engine.field.width / 2.0 - 0.2, 0.0);
+    engine.frisbee.launch(vector2::Vector2::new(1.0, 0.0), game_engine::INITIAL_FRISBEE_SPEED);
+    engine.players.1.pos = vector2::Vector2::new(engine.field.width / 2.0 - 0.5, engine.field.height / 2.0 - 0.1);
+    let mut result = engine.step_rl(0, 0);
+    for _ in 0..10 {
+        if result.done {
+            break;
+        }
+        result = engine.step_rl(0, 0);
+    }
+    assert!(result.done);
+    assert!(result.reward_left > 0.0);
+    assert_eq!(result.reward_right, -result.reward_left);
+    assert!(engine.players.0.score > 0);
+}
+
+#[test]
+fn test_vec_env() {
+    let mut envs = sim::VecEnv::new(&[7; 8]);
+    envs.reset_all();
+    let mut rewarded = false;
+    for frame in 0..2000u32 {
+        // Left walks about and throws, right stands still
+        let left = [1, 4, 9, 2, 12, 14][(frame / 20) as usize % 6];
+        let results = envs.step_all(&[(left, 0); 8]);
+        assert_eq!(results.len(), 8);
+        assert!(results.iter().all(|r| *r == results[0]));
+        rewarded |= results[0].reward_left != 0.0;
+    }
+    assert!(rewarded);
+    for env in envs.envs.iter() {
+        assert_eq!(env.players.0.pos, envs.envs[0].players.0.pos);
+        assert_eq!(env.frisbee.pos, envs.envs[0].frisbee.pos);
+    }
+}
+
+#[test]
+fn test_set_frisbee() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.players.1.pos = vector2::Vector2::new(5.0, 2.0);
+    engine.set_frisbee(vector2::Vector2::new(-3.0, 0.0), vector2::Vector2::new(0.1, 0.0), Some(player::PlayerSide::Right));
+    assert_eq!(engine.frisbee.held_by_player, Some(player::PlayerSide::Right));
+    assert_eq!(engine.frisbee.pos, engine.players.1.pos);
+
+    engine.step((agent::Intent::None, agent::Intent::Dash(vector2::Vector2::new(0.0, -1.0))));
+    engine.step((agent::Intent::None, agent::Intent::None));
+    assert_eq!(engine.frisbee.held_by_player, Some(player::PlayerSide::Right));
+    assert_eq!(engine.frisbee.pos, engine.players.1.pos);
+    assert!(engine.players.1.pos.y < 2.0);
+
+    // A free frisbee keeps the given velocity
+    engine.set_frisbee(vector2::Vector2::new(-3.0, 0.0), vector2::Vector2::new(0.1, 0.0), None);
+    assert!(engine.frisbee.velocity().approx_eq(&vector2::Vector2::new(0.1, 0.0), 1e-9));
+}
+
+#[test]
+fn test_set_player_pos() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.last_held = Some(player::PlayerSide::Right);
+    engine.set_frisbee(vector2::Vector2::new(-4.0, 1.0), vector2::Vector2::zero(), None);
+
+    // Crossing the net or leaving the field is clamped back
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(3.0, 100.0));
+    assert!(engine.players.0.pos.x < 0.0);
+    assert_eq!(engine.players.0.pos.y, engine.field.side_wall());
+
+    engine.set_player_pos(player::PlayerSide::Left, engine.frisbee.pos);
+    assert!(engine.can_catch(player::PlayerSide::Left));
+    engine.step((agent::Intent::None, agent::Intent::None));
+    assert_eq!(engine.frisbee.held_by_player, Some(player::PlayerSide::Left));
+
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-6.0, -2.0));
+    assert_eq!(engine.frisbee.pos, vector2::Vector2::new(-6.0, -2.0));
+}
+
+#[test]
+fn test_frames_elapsed() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    assert_eq!(engine.frames_elapsed(), 0);
+    for _ in 0..137 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert_eq!(engine.frames_elapsed(), 137);
+
+    engine.reset_episode();
+    assert_eq!(engine.frames_elapsed(), 0);
+}
+
+#[test]
+fn test_max_index_ties() {
+    use rand::SeedableRng;
+
+    let values = [1.5; agent::QVALUES_ACTIONS];
+    let mut counts = [0; agent::QVALUES_ACTIONS];
+    for seed in 0..1700 {
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(seed);
+        counts[agent::max_index(&values, &mut rng)] += 1;
+    }
+    // 100 expected for each index
+    assert!(counts.iter().all(|&count| count > 60 && count < 140), "{:?}", counts);
+
+    let mut values = [0.0; agent::QVALUES_ACTIONS];
+    values[3] = 2.0;
+    values[9] = 2.0;
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+    for _ in 0..50 {
+        let index = agent::max_index(&values, &mut rng);
+        assert!(index == 3 || index == 9);
+    }
+    assert_eq!(agent::max_indices(&values), vec![3, 9]);
+}
+
+#[test]
+fn test_action_space() {
+    assert_eq!(agent::ActionSpace::len(), agent::QVALUES_ACTIONS);
+    for index in 0..agent::ActionSpace::len() {
+        let input = agent::ActionSpace::to_human_intent(index);
+        assert_eq!(agent::ActionSpace::from_human_intent(input), index);
+        assert_eq!(agent::human_intent_to_index(agent::human_intent_from_index(index as u8)) as usize, index);
+    }
+    assert_eq!(agent::ActionSpace::from_human_intent(agent::HumanIntent::THROW), 0);
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset();
+    let up_left = agent::ActionSpace::from_human_intent(agent::HumanIntent::UP | agent::HumanIntent::LEFT);
+    match agent::ActionSpace::to_intent(up_left, &engine, player::PlayerSide::Left) {
+        agent::Intent::Move(dir) => assert!(dir.x < 0.0 && dir.y > 0.0),
+        intent => panic!("Expected a move, got {}", intent),
+    }
+}
+
+#[test]
+fn test_weighted_throw_direction() {
+    use rand::SeedableRng;
+
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(3);
+    for _ in 0..200 {
+        assert_eq!(frisbee::random_throw_direction_weighted(&[0.0, 0.0, 1.0, 0.0, 0.0], &mut rng), frisbee::ThrowDirection::Middle);
+    }
+
+    let mut middle = 0;
+    for _ in 0..800 {
+        if frisbee::random_throw_direction_weighted(&agent::MIDDLE_BIASED_THROW_WEIGHTS, &mut rng) == frisbee::ThrowDirection::Middle {
+            middle += 1;
+        }
+    }
+    // Half of the throws are expected in the middle
+    assert!(middle > 300 && middle < 500, "{}", middle);
+}
+
+#[test]
+fn test_difficulty_agent() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset_episode();
+    let mut curriculum = agent::DifficultyAgent::new(vec![
+        (0, Box::new(agent::RandomAgent::new())),
+        (2, Box::new(agent::SeekAgent {})),
+    ]);
+    let mut seek = agent::SeekAgent {};
+
+    for episode in 0..4 {
+        for _ in 0..30 {
+            let intent = engine.ask_agent(&mut curriculum, player::PlayerSide::Right);
+            if episode >= 2 {
+                assert_eq!(intent, engine.ask_agent(&mut seek, player::PlayerSide::Right));
+            }
+            engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+        }
+        assert_eq!(curriculum.episode(), episode);
+        let expected = if episode < 2 { agent::AgentType::Random } else { agent::AgentType::Seek };
+        assert_eq!(agent::Agent::get_type(&curriculum), expected);
+        engine.reset_episode();
+    }
+
+    // Episodes it isn't asked in, even without a single frame, still count
+    engine.reset();
+    engine.reset_episode();
+    engine.ask_agent(&mut curriculum, player::PlayerSide::Right);
+    assert_eq!(curriculum.episode(), 6);
+}
+
+#[test]
+fn test_try_agent_type_from_i8() {
+    assert!(agent::try_agent_type_from_i8(99).is_err());
+    assert!(agent::try_agent_type_from_i8(-1).is_err());
+    assert!(agent::try_agent_type_from_i8(agent::AgentType::None as i8).is_err());
+    assert_eq!(agent::try_agent_type_from_i8(4), Ok(agent::AgentType::TabularQLearning));
+    assert_eq!(agent::try_agent_type_from_i8(12), Ok(agent::AgentType::Seek));
+}
+
+#[test]
+fn test_metrics_csv() {
+    let path = ::std::env::temp_dir().join("rustjammers_test_metrics.csv");
+    let path = path.to_str().unwrap();
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset_episode();
+    engine.metrics = Some(sim::MetricsLogger::new());
+    for episode in 0..3 {
+        engine.explo_rate = 0.5 / (episode + 1) as f32;
+        engine.players.0.score = episode;
+        engine.reset_episode();
+    }
+    let metrics = engine.metrics.take().unwrap();
+    assert_eq!(metrics.episodes.len(), 3);
+    assert_eq!(metrics.episodes[2].left_score, 2);
+    metrics.to_csv(path).unwrap();
+
+    let csv = ::std::fs::read_to_string(path).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "episode,left_score,right_score,epsilon,nonzero_q_states");
+    assert_eq!(lines[1], "0,0,0,0.5,0");
+    assert_eq!(lines[3], "2,2,0,0.16666667,0");
+
+    ::std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_substeps() {
+    let field = game_engine::FieldConfig::default();
+    let mut fast = frisbee::Frisbee::new();
+    fast.config.max_speed = 40.0;
+    fast.launch(vector2::Vector2::new(1.0, 0.0), 40.0);
+
+    let mut single = fast;
+    collision::frisbee_move(&mut single, &field, 1.0);
+    let full_step = single.pos.distance(&fast.pos);
+    assert!((full_step - 4.0).abs() < 1e-9);
+
+    // Every quarter of a step moves a quarter of the way, ending at the same place
+    let mut split = fast;
+    let mut max_substep: f64 = 0.0;
+    for _ in 0..4 {
+        let before = split.pos;
+        collision::frisbee_move(&mut split, &field, 0.25);
+        max_substep = max_substep.max(split.pos.distance(&before));
+    }
+    assert!((max_substep - full_step / 4.0).abs() < 1e-9);
+    assert!(split.pos.approx_eq(&single.pos, 1e-9));
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.substeps = 4;
+    engine.frisbee.config.max_speed = 40.0;
+    engine.set_frisbee(vector2::Vector2::new(-2.0, 3.0), vector2::Vector2::new(0.0, 4.0), None);
+    let mut reference = engine.frisbee;
+    collision::frisbee_move_substeps(&mut reference, &engine.field, 4);
+    engine.step((agent::Intent::None, agent::Intent::None));
+    assert!(engine.frisbee.pos.approx_eq(&reference.pos, 1e-9));
+
+    // Too fast to be within reach of the player at either end of the step, only the substeps catch it
+    for &(substeps, catches) in [(1, false), (4, true)].iter() {
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.substeps = substeps;
+        engine.frisbee.config.max_speed = 60.0;
+        engine.take_events();
+        engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-8.0, 4.0));
+        engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(1.5, 0.0));
+        engine.set_frisbee(vector2::Vector2::new(-3.0, 0.0), vector2::Vector2::new(6.0, 0.0), None);
+        engine.frisbee.last_held = Some(player::PlayerSide::Left);
+        engine.step((agent::Intent::None, agent::Intent::None));
+        assert_eq!(engine.frisbee.held_by_player == Some(player::PlayerSide::Right), catches);
+        assert_eq!(engine.take_events().contains(&game_engine::GameEvent::Caught(player::PlayerSide::Right)), catches);
+    }
+}
+
+#[test]
+fn test_timed_agent() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset_episode();
+    let mut timed = agent::TimedAgent::new(agent::RandomAgent::new());
+    assert_eq!(timed.count(), 0);
+    assert_eq!(timed.p99(), ::std::time::Duration::from_secs(0));
+
+    for _ in 0..20 {
+        engine.ask_agent(&mut timed, player::PlayerSide::Left);
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert_eq!(timed.count(), 20);
+    assert!(timed.mean() <= timed.max());
+    assert!(timed.p99() <= timed.max());
+    assert_eq!(agent::Agent::get_type(&timed), agent::AgentType::Random);
+}
+
+#[test]
+fn test_dump_q_values_json() {
+    let path = ::std::env::temp_dir().join("rustjammers_test_q_values.json");
+    let path = path.to_str().unwrap();
+
+    let mut engine = game_engine::GameEngine::new();
+    for i in 0..10 {
+        engine.q_values.insert(i, ([0.0; agent::QVALUES_ACTIONS], [0.0; agent::QVALUES_ACTIONS]));
+    }
+    engine.q_values.get_mut(&7).unwrap().1[2] = -1.5;
+
+    engine.dump_q_values_json(path, true).unwrap();
+    let json = ::std::fs::read_to_string(path).unwrap();
+    assert_eq!(json.matches("\"left\"").count(), 1);
+    assert!(json.contains("\"7\": { \"left\": [0, 0,"));
+    assert!(json.contains("\"right\": [0, 0, -1.5, 0,"));
+
+    engine.dump_q_values_json(path, false).unwrap();
+    let json = ::std::fs::read_to_string(path).unwrap();
+    assert_eq!(json.matches("\"left\"").count(), 10);
+    assert!(json.find("\"0\"").unwrap() < json.find("\"9\"").unwrap());
+
+    ::std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_intent_history() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset_episode();
+    engine.step((agent::Intent::Move(vector2::Vector2::new(1.0, 0.0)), agent::Intent::None));
+    assert!(engine.intent_history(player::PlayerSide::Left).is_empty());
+
+    engine.set_intent_history_capacity(3);
+    let moves = [
+        agent::Intent::Move(vector2::Vector2::new(0.0, 1.0)),
+        agent::Intent::Move(vector2::Vector2::new(1.0, 0.0)),
+        agent::Intent::Move(vector2::Vector2::new(0.0, -1.0)),
+    ];
+    // The oldest intent is dropped once the history is full
+    engine.step((agent::Intent::None, agent::Intent::None));
+    for intent in moves.iter() {
+        engine.step((*intent, agent::Intent::None));
+    }
+    assert_eq!(engine.intent_history(player::PlayerSide::Left).iter().cloned().collect::<Vec<_>>(), moves.to_vec());
+    assert_eq!(engine.intent_history(player::PlayerSide::Right).len(), 3);
+
+    engine.set_intent_history_capacity(1);
+    assert_eq!(engine.intent_history(player::PlayerSide::Left).front(), Some(&moves[2]));
+    engine.reset_episode();
+    assert!(engine.intent_history(player::PlayerSide::Left).is_empty());
+}
+
+#[test]
+fn test_dash_frames_remaining() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset_episode();
+    assert_eq!(engine.dash_frames_remaining(player::PlayerSide::Left), 0);
+
+    let duration = engine.players.0.config.dash_duration_frames;
+    engine.players.0.dash(vector2::Vector2::new(0.0, 10.0));
+    assert_eq!(engine.dash_frames_remaining(player::PlayerSide::Left), duration);
+    assert_eq!(engine.observation(player::PlayerSide::Left)[11], 1.0);
+    assert_eq!(engine.observation(player::PlayerSide::Right)[12], 1.0);
+
+    let mut copy = game_engine::GameEngine::new();
+    engine.copy_in(&mut copy);
+    assert_eq!(copy.dash_frames_remaining(player::PlayerSide::Left), duration);
+
+    for left in (0..duration).rev() {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+        assert_eq!(engine.dash_frames_remaining(player::PlayerSide::Left), left);
+    }
+    assert!(engine.players.0.slide.is_none());
+}
+
+#[test]
+fn test_hash_frisbee_heading() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.reset_episode();
+    engine.set_frisbee(vector2::Vector2::new(2.0, 1.0), vector2::Vector2::new(0.2, 0.0), None);
+    let forward = engine.hash();
+    engine.set_frisbee(vector2::Vector2::new(2.0, 1.0), vector2::Vector2::new(-0.2, 0.0), None);
+    let backward = engine.hash();
+    engine.set_frisbee(vector2::Vector2::new(2.0, 1.0), vector2::Vector2::zero(), None);
+    let still = engine.hash();
+    assert_ne!(forward, backward);
+    assert_ne!(forward, still);
+    assert_ne!(backward, still);
+
+    // Mirrored for the right player, flying toward the left goal is flying forward
+    engine.players.1.pos = vector2::Vector2::new(-engine.players.0.pos.x, engine.players.0.pos.y);
+    engine.set_frisbee(vector2::Vector2::new(-2.0, 1.0), vector2::Vector2::new(-0.2, 0.0), None);
+    assert_eq!(engine.hash_canonical(player::PlayerSide::Right), forward);
+}
+
+#[test]
+fn test_throw_params() {
+    fn thrown_distance(power: Option<game_engine::ThrowPower>) -> f64 {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.frisbee.config.max_speed = 5.0;
+        if let Some(power) = power {
+            engine.set_throw_power(frisbee::ThrowDirection::Up, power);
+        }
+        engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-8.0, -4.0));
+        engine.set_frisbee(vector2::Vector2::zero(), vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+        let start = engine.frisbee.pos;
+        engine.step((agent::Intent::Throw(frisbee::ThrowDirection::Up), agent::Intent::None));
+        for _ in 1..10 {
+            engine.step((agent::Intent::None, agent::Intent::None));
+        }
+        assert!(engine.frisbee.held_by_player.is_none());
+        engine.frisbee.pos.distance(&start)
+    }
+
+    let default = thrown_distance(None);
+    let faster = game_engine::ThrowPower { speed: 4.0, ..game_engine::ThrowPower::default() };
+    assert!(thrown_distance(Some(faster)) > default);
+
+    // Biased straight up, the throw climbs steeper
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.set_throw_power(frisbee::ThrowDirection::Middle, game_engine::ThrowPower { vertical_bias: 1.0, ..game_engine::ThrowPower::default() });
+    engine.set_frisbee(vector2::Vector2::zero(), vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+    engine.step((agent::Intent::Throw(frisbee::ThrowDirection::Middle), agent::Intent::None));
+    assert!(engine.frisbee.direction.approx_eq(&vector2::Vector2::new(1.0, 1.0).normalized(), 1e-9));
+}
+
+#[test]
+fn test_act_ranked() {
+    fn scenario() -> game_engine::GameEngine {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.seed_rng(11);
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-2.0, 0.0));
+        engine.set_frisbee(vector2::Vector2::zero(), vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+        engine
+    }
+
+    let mut rollout = agent::RandomRolloutAgent { commit_frames: 1, playout_frames: 60.0, sim: 2, throws: frisbee::THROW_DIRECTIONS.to_vec(), curved_throws: false, opponent_policy: agent::AgentType::HumanPlayer };
+    let ranked = rollout.act_ranked(player::PlayerSide::Left, &mut scenario());
+    assert!(ranked.len() >= 5);
+    assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+    // Nobody defends, a straight throw scores
+    assert!(ranked[0].1 > 0.0);
+    assert_eq!(ranked[0].0, agent::Agent::act(&mut rollout, player::PlayerSide::Left, &mut scenario()));
+
+    let mut dijkstra = agent::DijkstraAgent::new(2);
+    let ranked = dijkstra.act_ranked(player::PlayerSide::Left, &mut scenario());
+    assert!(ranked.len() > 1);
+    assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+    assert_eq!(ranked[0].0, agent::Agent::act(&mut dijkstra, player::PlayerSide::Left, &mut scenario()));
+}
+
+#[test]
+fn test_paused() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::Random as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.seed_rng(4);
+    engine.reset();
+    for _ in 0..100 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert!(engine.state_of_game == game_engine::StateOfGame::Playing);
+
+    engine.set_paused(true);
+    assert!(engine.is_paused());
+    let (players, frisbee, frame, time) = (engine.players, engine.frisbee.pos, engine.frames_elapsed(), engine.time);
+    for _ in 0..50 {
+        engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    }
+    assert_eq!(engine.players.0.pos, players.0.pos);
+    assert_eq!(engine.players.1.pos, players.1.pos);
+    assert_eq!(engine.frisbee.pos, frisbee);
+    assert_eq!(engine.frames_elapsed(), frame);
+    assert_eq!(engine.time, time);
+    assert_eq!(game_engine::state_to_i8(&engine.state_of_game), 4);
+
+    engine.set_paused(false);
+    assert!(engine.state_of_game == game_engine::StateOfGame::Playing);
+    engine.epoch(agent::HumanIntent::IDLE, agent::HumanIntent::IDLE);
+    assert_eq!(engine.frames_elapsed(), frame + 1);
+
+    // Resuming a match that isn't paused changes nothing
+    engine.set_paused(false);
+    assert!(engine.state_of_game == game_engine::StateOfGame::Playing);
+}
+
+#[test]
+fn test_simulate_intent() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::Random as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-1.0, 0.0));
+    engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(5.0, 4.0));
+    engine.set_frisbee(vector2::Vector2::zero(), vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+    let before = engine.players.0.score;
+
+    // The opponent is held still, nobody is in the way
+    let score = agent::simulate_intent(&mut engine, player::PlayerSide::Left, agent::Intent::Throw(frisbee::ThrowDirection::Middle), 60, agent::Intent::None);
+    assert!(score > before);
+    assert_eq!(score, engine.players.0.score - engine.players.1.score);
+    assert_eq!(engine.players.1.pos, vector2::Vector2::new(5.0, 4.0));
+}
+
+#[test]
+fn test_simulate_forced_opponent_not_asked() {
+    struct Counting(::std::sync::Arc<::std::sync::atomic::AtomicUsize>);
+    impl agent::Agent for Counting {
+        fn act(&mut self, _side: player::PlayerSide, _engine: &mut game_engine::GameEngine) -> agent::Intent {
+            self.0.fetch_add(1, ::std::sync::atomic::Ordering::SeqCst);
+            agent::Intent::None
+        }
+        fn get_type(&self) -> agent::AgentType {
+            agent::AgentType::Random
+        }
+    }
+
+    let asked = (::std::sync::Arc::new(::std::sync::atomic::AtomicUsize::new(0)), ::std::sync::Arc::new(::std::sync::atomic::AtomicUsize::new(0)));
+    let mut engine = game_engine::GameEngine::new();
+    engine.agents = (Some(Box::new(Counting(asked.0.clone()))), Some(Box::new(Counting(asked.1.clone()))));
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.set_frisbee(vector2::Vector2::new(0.0, 4.0), vector2::Vector2::zero(), None);
+
+    // The right player's intent is forced, its agent is never asked for one
+    agent::simulate_committed(&mut engine, player::PlayerSide::Left, agent::Intent::None, 5, 10, agent::Intent::None);
+    assert_eq!(asked.0.load(::std::sync::atomic::Ordering::SeqCst), 10);
+    assert_eq!(asked.1.load(::std::sync::atomic::Ordering::SeqCst), 0);
+
+    // Without a forced intent only the opponent is asked while the left intent is committed
+    agent::simulate_against_agent(&mut engine, player::PlayerSide::Left, agent::Intent::None, 5, 0);
+    assert_eq!(asked.0.load(::std::sync::atomic::Ordering::SeqCst), 10);
+    assert_eq!(asked.1.load(::std::sync::atomic::Ordering::SeqCst), 4);
+}
+
+#[test]
+fn test_normalize_zero() {
+    let zero = vector2::Vector2::zero().normalized();
+    assert_eq!(zero, vector2::Vector2::zero());
+    assert!(!zero.x.is_nan() && !zero.y.is_nan());
+
+    // Too short to have a meaningful direction
+    assert_eq!(vector2::Vector2::new(1e-200, -1e-200).normalized(), vector2::Vector2::zero());
+    let mut tiny = vector2::Vector2::new(1e-13, 0.0);
+    tiny.normalize();
+    assert_eq!(tiny, vector2::Vector2::zero());
+
+    assert_eq!(vector2::Vector2::new(0.0, -3.0).normalized(), vector2::Vector2::new(0.0, -1.0));
+}
+
+#[test]
+fn test_grab() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.manual_catch = true;
+    engine.frisbee.last_held = Some(player::PlayerSide::Right);
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-6.0, 0.0));
+
+    // Out of reach the grab does nothing
+    engine.set_frisbee(vector2::Vector2::new(-3.0, 0.0), vector2::Vector2::zero(), None);
+    engine.step((agent::Intent::Grab, agent::Intent::None));
+    assert!(engine.frisbee.held_by_player.is_none());
+    assert_eq!(engine.players.0.pos, vector2::Vector2::new(-6.0, 0.0));
+
+    // In reach, standing by isn't enough
+    engine.set_frisbee(vector2::Vector2::new(-5.5, 0.0), vector2::Vector2::zero(), None);
+    engine.step((agent::Intent::None, agent::Intent::None));
+    assert!(engine.frisbee.held_by_player.is_none());
+    assert!(engine.legal_intents(player::PlayerSide::Left).contains(&agent::Intent::Grab));
+
+    let grab = agent::ActionSpace::from_human_intent(agent::HumanIntent::GRAB);
+    assert_eq!(agent::ActionSpace::to_intent(grab, &engine, player::PlayerSide::Left), agent::Intent::Grab);
+    engine.step_with_actions(grab as u8, 0);
+    assert_eq!(engine.frisbee.held_by_player, Some(player::PlayerSide::Left));
+}
+
+#[test]
+fn test_rng_for() {
+    fn left_intents(right_draws: bool) -> Vec<agent::Intent> {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.seed_rng(42);
+        let mut left = agent::RandomAgent::new();
+        let mut right = agent::RandomAgent::new();
+        let mut intents = Vec::new();
+        for _ in 0..50 {
+            intents.push(agent::Agent::act(&mut left, player::PlayerSide::Left, &mut engine));
+            if right_draws {
+                agent::Agent::act(&mut right, player::PlayerSide::Right, &mut engine);
+            }
+        }
+        intents
+    }
+
+    // The right agent consuming its stream leaves the left one's untouched
+    assert!(left_intents(true) == left_intents(false));
+
+    let mut engine = game_engine::GameEngine::new();
+    engine.seed_rng(42);
+    let left: u64 = rand::Rng::gen(engine.rng_for(player::PlayerSide::Left));
+    let right: u64 = rand::Rng::gen(engine.rng_for(player::PlayerSide::Right));
+    assert_ne!(left, right);
+}
+
+#[test]
+fn test_parse_agent_spec() {
+    let (agent_type, params) = agent::parse_agent_spec("dijkstra:depth=5").unwrap();
+    assert_eq!(agent_type, agent::AgentType::Dijkstra);
+    assert_eq!(params, agent::AgentParams { sim: 5, ..agent::AgentParams::default() });
+
+    let (agent_type, params) = agent::parse_agent_spec("rollout:frames=30, sim=4,time_ms=2").unwrap();
+    assert_eq!(agent_type, agent::AgentType::RandomRollout);
+    assert_eq!(params, agent::AgentParams { frames: 30.0, sim: 4, time_budget: ::std::time::Duration::from_millis(2) });
+
+    let (left, right) = agent::parse_matchup("dijkstra:depth=5 vs qlearn").unwrap();
+    assert_eq!(left.0, agent::AgentType::Dijkstra);
+    assert_eq!(right, (agent::AgentType::TabularQLearning, agent::AgentParams::default()));
+
+    assert_eq!(agent::parse_agent_spec("dijkstra:width=5"), Err("Unknown key width for agent dijkstra.".to_string()));
+    assert!(agent::parse_agent_spec("dijkstra:depth=deep").is_err());
+    assert!(agent::parse_agent_spec("chess").is_err());
+    assert!(agent::parse_matchup("dijkstra").is_err());
+}
+
+#[test]
+fn test_carry_speed_multiplier() {
+    fn dash_distance(carrying: bool) -> f64 {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.set_player_config(player::PlayerSide::Left, player::PlayerConfig { carry_speed_multiplier: 0.5, ..player::PlayerConfig::default() });
+        let start = vector2::Vector2::new(-6.0, 0.0);
+        engine.set_player_pos(player::PlayerSide::Left, start);
+        if carrying {
+            engine.set_frisbee(start, vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+        } else {
+            engine.set_frisbee(vector2::Vector2::new(6.0, 0.0), vector2::Vector2::zero(), None);
+        }
+        engine.step((agent::Intent::Dash(vector2::Vector2::new(0.0, 1.0)), agent::Intent::None));
+        (engine.players.0.pos - start).length()
+    }
+
+    let empty_handed = dash_distance(false);
+    assert!(empty_handed > 0.0);
+    assert!((dash_distance(true) - empty_handed * 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_block_mode() {
+    fn block(mode: game_engine::BlockMode, intent: agent::Intent) -> game_engine::GameEngine {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.block_mode = mode;
+        engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(6.0, 0.0));
+        engine.set_frisbee(vector2::Vector2::new(5.5, 0.0), vector2::Vector2::new(0.25, 0.0), None);
+        engine.frisbee.last_held = Some(player::PlayerSide::Left);
+        engine.step((agent::Intent::None, intent));
+        engine
+    }
+
+    let engine = block(game_engine::BlockMode::Catch, agent::Intent::None);
+    assert_eq!(engine.frisbee.held_by_player, Some(player::PlayerSide::Right));
+    assert!(!engine.requires_grab());
+
+    let engine = block(game_engine::BlockMode::Bounce, agent::Intent::None);
+    assert!(engine.frisbee.held_by_player.is_none());
+    assert!(engine.frisbee.velocity().x < 0.0);
+    assert_eq!(engine.frisbee.last_held, Some(player::PlayerSide::Right));
+    assert!(engine.legal_intents(player::PlayerSide::Right).contains(&agent::Intent::Grab));
+
+    let engine = block(game_engine::BlockMode::PassThrough, agent::Intent::None);
+    assert!(engine.frisbee.held_by_player.is_none());
+    assert!(engine.frisbee.velocity().x > 0.0);
+    assert_eq!(engine.frisbee.last_held, Some(player::PlayerSide::Left));
+
+    // Grabbing catches it whatever the mode
+    let engine = block(game_engine::BlockMode::Bounce, agent::Intent::Grab);
+    assert_eq!(engine.frisbee.held_by_player, Some(player::PlayerSide::Right));
+}
+
+#[test]
+fn test_render_ascii() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.set_frisbee(vector2::Vector2::new(0.0, 2.0), vector2::Vector2::zero(), None);
+    engine.players.1.score = 3;
+
+    let board = engine.render_ascii();
+    assert!(board.contains('L'));
+    assert!(board.contains('R'));
+    assert!(board.contains('*'));
+    let lines: Vec<&str> = board.lines().collect();
+    // Two borders and the score around the field rows
+    assert_eq!(lines.len(), engine.field.height.ceil() as usize + 3);
+    assert_eq!(lines[1].len(), (engine.field.width * 2.0).ceil() as usize + 2);
+    assert_eq!(*lines.last().unwrap(), "L 0 - 3 R");
+
+    // A held frisbee is hidden by its holder
+    engine.set_frisbee(engine.players.0.pos, vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+    assert!(!engine.render_ascii().contains('*'));
+}
+
+#[test]
+fn test_commit_and_playout_frames() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-6.0, 0.0));
+    engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(6.0, 3.0));
+    engine.set_frisbee(vector2::Vector2::new(-6.0, 0.0), vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+
+    let middle = agent::Intent::Throw(frisbee::ThrowDirection::Middle);
+    let score = |playout_frames: f64| {
+        let rollout = agent::RandomRolloutAgent {
+            commit_frames: 1,
+            playout_frames,
+            sim: 1,
+            throws: vec![frisbee::ThrowDirection::Middle],
+            curved_throws: false,
+            opponent_policy: agent::AgentType::HumanPlayer,
+        };
+        rollout.ranked(player::PlayerSide::Left, &engine, 5).into_iter().find(|r| r.0 == middle).map_or(0.0, |r| r.1)
+    };
+    // The throw only reaches the goal in a long enough playout
+    assert_eq!(score(10.0), 0.0);
+    assert!(score(120.0) > 0.0);
+
+    // The committed intent is played on every committed frame
+    let walk = agent::Intent::Move(vector2::Vector2::new(0.0, 1.0));
+    let mut once = game_engine::GameEngine::new();
+    engine.frisbee.held_by_player = None;
+    engine.frisbee.pos = vector2::Vector2::new(0.0, -3.0);
+    engine.copy_in(&mut once);
+    let mut committed = game_engine::GameEngine::new();
+    engine.copy_in(&mut committed);
+    agent::simulate_committed(&mut once, player::PlayerSide::Left, walk, 1, 0, agent::Intent::None);
+    agent::simulate_committed(&mut committed, player::PlayerSide::Left, walk, 5, 0, agent::Intent::None);
+    let walked = |engine: &game_engine::GameEngine| engine.players.0.pos.y;
+    assert!((walked(&committed) - walked(&engine) - 5.0 * (walked(&once) - walked(&engine))).abs() < 1e-9);
+}
+
+#[test]
+fn test_shared_q_values() {
+    let shared = agent::SharedQValues::default();
+    let workers: Vec<_> = (0..2u64).map(|worker| {
+        let shared = shared.clone();
+        ::std::thread::spawn(move || {
+            let mut engine = game_engine::GameEngine::new();
+            engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+            engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+            engine.reset();
+            let mut q_agent = agent::TabularQLearningAgent::with_shared(1, shared);
+            // Each worker rewards its own states
+            for state in worker * 1000..worker * 1000 + 100 {
+                q_agent.previous = Some((state, 0));
+                engine.rewards.0 = 1.0;
+                agent::Agent::act(&mut q_agent, player::PlayerSide::Left, &mut engine);
+            }
+            assert!(engine.q_values.is_empty());
+        })
+    }).collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let q_values = shared.read().unwrap();
+    for state in (0..100).chain(1000..1100) {
+        assert!(q_values[&state].0[0] > 0.0);
+    }
+}
+
+#[test]
+fn test_rollout_early_termination() {
+    let scenario = || {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.seed_rng(11);
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-2.0, 0.0));
+        engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(6.0, 3.5));
+        engine.set_frisbee(vector2::Vector2::zero(), vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+        engine
+    };
+    let throws = vec![frisbee::ThrowDirection::Middle, frisbee::ThrowDirection::Up, frisbee::ThrowDirection::Down];
+    let mut rollout = agent::RandomRolloutAgent { commit_frames: 1, playout_frames: 60.0, sim: 4, throws, curved_throws: false, opponent_policy: agent::AgentType::HumanPlayer };
+    let middle = agent::Intent::Throw(frisbee::ThrowDirection::Middle);
+
+    // The straight throw scores in the 5 points zone on the very first rollout
+    let engine = scenario();
+    let (intent, count) = rollout.search_serial_counted(player::PlayerSide::Left, &engine, 3);
+    assert_eq!(intent, middle);
+    assert_eq!(count, 1);
+    assert_eq!(intent, rollout.ranked(player::PlayerSide::Left, &engine, 3)[0].0);
+    assert_eq!(agent::Agent::act(&mut rollout, player::PlayerSide::Left, &mut scenario()), middle);
+}
+
+#[test]
+fn test_rollout_when_trailing() {
+    let scenario = || {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.seed_rng(11);
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.players.1.score = 10;
+        engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-2.0, 0.0));
+        engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(6.0, 3.5));
+        engine.set_frisbee(vector2::Vector2::zero(), vector2::Vector2::zero(), Some(player::PlayerSide::Left));
+        engine
+    };
+    let throws = vec![frisbee::ThrowDirection::Middle, frisbee::ThrowDirection::Up, frisbee::ThrowDirection::Down];
+    let mut rollout = agent::RandomRolloutAgent { commit_frames: 1, playout_frames: 60.0, sim: 4, throws, curved_throws: false, opponent_policy: agent::AgentType::HumanPlayer };
+    let middle = agent::Intent::Throw(frisbee::ThrowDirection::Middle);
+
+    // Still behind after the goal, the throw is worth it all the same
+    let engine = scenario();
+    let (intent, count) = rollout.search_serial_counted(player::PlayerSide::Left, &engine, 3);
+    assert_eq!(intent, middle);
+    assert_eq!(count, 1);
+    assert_eq!(rollout.ranked(player::PlayerSide::Left, &engine, 3)[0], (middle, 5.0));
+    assert_eq!(agent::Agent::act(&mut rollout, player::PlayerSide::Left, &mut scenario()), middle);
+
+    let mut timed = agent::TimedRolloutAgent::new(60.0, ::std::time::Duration::from_millis(10));
+    timed.throws = vec![frisbee::ThrowDirection::Middle];
+    assert_eq!(timed.search(player::PlayerSide::Left, &engine, 3).0, middle);
+}
+
+#[test]
+fn test_players_never_overlap() {
+    let walk_into_each_other = |player_collision: bool| {
+        let mut engine = game_engine::GameEngine::new();
+        engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.player_collision = player_collision;
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.set_frisbee(vector2::Vector2::new(0.0, 4.0), vector2::Vector2::zero(), None);
+        engine.frisbee.last_held = Some(player::PlayerSide::Left);
+        for _ in 0..200 {
+            engine.step((agent::Intent::Move(vector2::Vector2::new(1.0, 0.0)), agent::Intent::Move(vector2::Vector2::new(-1.0, 0.0))));
+        }
+        assert!(engine.players.0.pos.x <= 0.0 && engine.players.1.pos.x >= 0.0);
+        (engine.players.1.pos - engine.players.0.pos).length()
+    };
+
+    // Each one stops on its side of the net
+    assert!(walk_into_each_other(true) >= 2.0 * collision::NET - 1e-9);
+    // Without collisions they meet on the middle line
+    assert!(walk_into_each_other(false) < 2.0 * collision::PLAYER_RADIUS);
+
+    // Overlapping players are pushed apart along the line between them, each by half the overlap
+    let mut players = (player::Player::new(), player::Player::new());
+    players.0.pos = vector2::Vector2::new(-0.3, 0.0);
+    players.1.pos = vector2::Vector2::new(0.3, 0.0);
+    assert!(collision::separate_players(&mut players));
+    assert!(players.0.pos.approx_eq(&vector2::Vector2::new(-0.5, 0.0), 1e-9));
+    assert!(players.1.pos.approx_eq(&vector2::Vector2::new(0.5, 0.0), 1e-9));
+    assert!(!collision::separate_players(&mut players));
+}
+
+#[test]
+fn test_agent_type_metadata() {
+    let mut names = Vec::new();
+    for i in 0..agent::AgentType::None as i8 {
+        let agent_type = agent::agent_type_from_i8(i);
+        assert!(!agent_type.name().is_empty());
+        assert!(!agent_type.description().is_empty());
+        assert_eq!(agent_type.is_learning(), agent::uses_q_values(agent_type));
+        names.push(agent_type.name());
+    }
+    assert!(!agent::AgentType::None.name().is_empty());
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), agent::AgentType::None as usize);
+    assert!(agent::AgentType::TabularQLearning.is_learning());
+    assert!(!agent::AgentType::Dijkstra.is_learning());
+}
+
+#[test]
+fn test_predict_frisbee() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.frisbee.config.drag = 0.01;
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-8.0, -3.0));
+    engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(8.0, -3.0));
+    // Heading up the field toward the side wall
+    engine.set_frisbee(vector2::Vector2::new(-3.0, 2.0), vector2::Vector2::new(0.1, 0.2), None);
+    engine.frisbee.last_held = Some(player::PlayerSide::Left);
+
+    let frames = 30;
+    let start = engine.frisbee.pos;
+    let predicted = engine.predict_frisbee(frames);
+    assert_eq!(predicted.len(), frames as usize);
+    // Predicting leaves the engine untouched
+    assert_eq!(engine.frisbee.pos, start);
+
+    // Reflected off the wall: the path goes up to it and comes back down
+    let highest = predicted.iter().map(|p| p.y).fold(f64::MIN, f64::max);
+    assert!((highest - engine.field.side_wall()).abs() < 0.25);
+    assert!(predicted.last().unwrap().y < highest);
+    assert!(predicted.windows(2).all(|w| w[1].x > w[0].x));
+
+    for _ in 0..frames {
+        engine.step((agent::Intent::None, agent::Intent::None));
+    }
+    assert!(engine.frisbee.held_by_player.is_none());
+    assert!(engine.frisbee.pos.approx_eq(predicted.last().unwrap(), 1e-9));
+}
+
+#[test]
+fn test_boltzmann_exploration() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::SmallRng::seed_from_u64(9);
+    let mut values = [0.5; agent::QVALUES_ACTIONS];
+    values[3] = 1.0;
+    let argmax_count = |temperature: f32, rng: &mut rand::rngs::SmallRng| {
+        (0..1000).filter(|_| agent::boltzmann_index(&values, temperature, rng) == 3).count()
+    };
+    // Near uniform when hot, only the best action when cold
+    assert!(argmax_count(10.0, &mut rng) < 200);
+    assert!(argmax_count(0.1, &mut rng) > argmax_count(1.0, &mut rng));
+    assert_eq!(argmax_count(0.01, &mut rng), 1000);
+    assert_eq!(argmax_count(0.0, &mut rng), 1000);
+
+    // The agent samples from its table when the engine is set to Boltzmann exploration
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.seed_rng(4);
+    engine.exploration = game_engine::ExplorationStrategy::Boltzmann;
+    let hash = engine.hash();
+    engine.q_values.insert(hash, (values, [0.0; agent::QVALUES_ACTIONS]));
+    let mut q_agent = agent::TabularQLearningAgent { temperature: 0.01, ..agent::TabularQLearningAgent::new() };
+    for _ in 0..20 {
+        agent::Agent::act(&mut q_agent, player::PlayerSide::Left, &mut engine);
+        assert_eq!(engine.inputs.0, agent::ActionSpace::to_human_intent(3));
+    }
+}
+
+#[test]
+fn test_rollout_score_differential() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.reset();
+    engine.state_of_game = game_engine::StateOfGame::Playing;
+    engine.set_player_pos(player::PlayerSide::Left, vector2::Vector2::new(-8.0, 0.0));
+    engine.set_player_pos(player::PlayerSide::Right, vector2::Vector2::new(5.0, 3.0));
+    // Thrown by the right player straight at the left goal
+    engine.set_frisbee(vector2::Vector2::new(-2.0, 0.0), vector2::Vector2::new(-0.25, 0.0), None);
+    engine.frisbee.last_held = Some(player::PlayerSide::Right);
+
+    // Both walk for the whole rollout, only one of them steps out of the frisbee's way
+    let rollout = agent::RandomRolloutAgent {
+        commit_frames: 60,
+        playout_frames: 0.0,
+        sim: 1,
+        throws: frisbee::THROW_DIRECTIONS.to_vec(),
+        curved_throws: false,
+        opponent_policy: agent::AgentType::HumanPlayer,
+    };
+    let ranked = rollout.ranked(player::PlayerSide::Left, &engine, 1);
+    let rank = |intent: agent::Intent| ranked.iter().position(|r| r.0 == intent).unwrap();
+    let toward = agent::Intent::Move(vector2::Vector2::new(1.0, 0.0));
+    let away = agent::Intent::Move(vector2::Vector2::new(0.0, 1.0));
+    assert!(rank(toward) < rank(away));
+    assert!(ranked[rank(away)].1 < 0.0);
+}
+
+#[test]
+fn test_small_field_spawns() {
+    let field = game_engine::FieldConfig::new(8.0, 4.0, 4.0);
+    let mut engine = game_engine::GameEngine::with_config(field);
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    let inside = |pos: vector2::Vector2, margin: f64| pos.x.abs() <= field.goal_line() - margin && pos.y.abs() <= field.side_wall();
+
+    engine.reset();
+    assert!(inside(engine.players.0.pos, 0.5) && engine.players.0.pos.x < 0.0);
+    assert!(inside(engine.players.1.pos, 0.5) && engine.players.1.pos.x > 0.0);
+    assert!(inside(engine.frisbee.pos, 0.0));
+
+    engine.reset_episode();
+    assert!(inside(engine.players.0.pos, 0.5) && inside(engine.players.1.pos, 0.5));
+
+    // Scored above the right player, then the players head back to their spawns and the frisbee waits inside the field
+    engine.set_frisbee(vector2::Vector2::new(1.0, 1.5), vector2::Vector2::new(0.25, 0.0), None);
+    engine.frisbee.last_held = Some(player::PlayerSide::Left);
+    for _ in 0..20 {
+        engine.step((agent::Intent::None, agent::Intent::None));
+    }
+    assert_eq!(engine.players.0.score, 5);
+    assert!(inside(engine.frisbee.pos, 0.0));
+    for _ in 0..100 {
+        engine.step((agent::Intent::None, agent::Intent::None));
+    }
+    assert_eq!(engine.players.0.pos, field.spawn(player::PlayerSide::Left));
+    assert_eq!(engine.players.1.pos, field.spawn(player::PlayerSide::Right));
+}
+
+#[test]
+fn test_reset_clears_dash() {
+    let mut engine = game_engine::GameEngine::new();
+    engine.send_type_p1(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    engine.send_type_p2(agent::AgentType::HumanPlayer as i8, 0.0, 0);
+    let config = player::PlayerConfig { dash_cooldown_frames: 50, ..player::PlayerConfig::default() };
+    engine.set_player_config(player::PlayerSide::Left, config);
+    engine.set_player_config(player::PlayerSide::Right, config);
+
+    for &full_reset in [true, false].iter() {
+        engine.reset();
+        engine.state_of_game = game_engine::StateOfGame::Playing;
+        engine.set_frisbee(vector2::Vector2::new(0.0, 4.0), vector2::Vector2::zero(), None);
+        engine.frisbee.last_held = Some(player::PlayerSide::Left);
+        // One player mid-dash, the other on cooldown
+        engine.step((agent::Intent::Dash(vector2::Vector2::new(0.0, 1.0)), agent::Intent::Dash(vector2::Vector2::new(0.0, 1.0))));
+        engine.players.1.end_slide();
+        assert!(!engine.can_dash(player::PlayerSide::Left) && !engine.can_dash(player::PlayerSide::Right));
+
+        if full_reset {
+            engine.reset();
+        } else {
+            engine.reset_episode();
+        }
+        assert!(engine.can_dash(player::PlayerSide::Left));
+        assert!(engine.can_dash(player::PlayerSide::Right));
+    }
+}
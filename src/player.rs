@@ -1,7 +1,8 @@
 use vector2::Vector2;
 use frisbee::ThrowDirection;
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PlayerSide {
     Left = 0,
     Right = 1
@@ -22,11 +23,36 @@ pub fn player_side_from_i8(side: i8) -> Option<PlayerSide> {
     }
 }
 
+// Speeds are in the same units as the frisbee's, a player covers `speed * 0.1` per frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayerConfig {
+    pub move_speed:           f64,
+    pub dash_speed:           f64,
+    pub dash_duration_frames: u32, // A dash stops after this many frames even if short of its target
+    pub dash_cooldown_frames: u32, // Frames to wait after a dash before dashing again
+    pub carry_speed_multiplier: f64, // Scales dashes while holding the frisbee, holders can't walk anyway
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            move_speed:           1.0,
+            dash_speed:           4.0,
+            dash_duration_frames: 7,
+            dash_cooldown_frames: 0,
+            carry_speed_multiplier: 1.0,
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Slide {
     pub target: Vector2,
     pub origin: Vector2,
     pub dir: Vector2,
+    pub frames_left: Option<u32>, // None for slides that must reach their target, like respawns
 }
 
 impl Slide {
@@ -39,11 +65,20 @@ impl Slide {
 }
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Player {
     pub pos:   Vector2,
     pub side:  Option<PlayerSide>,
     pub score: i8,
     pub slide: Option<Slide>,
+    pub config: PlayerConfig,
+    pub dash_cooldown: u32,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Player {
@@ -53,11 +88,13 @@ impl Player {
             side:  None,
             score: 0,
             slide: None,
+            config: PlayerConfig::default(),
+            dash_cooldown: 0,
         }
     }
 
     pub fn get_horizontal_position(&self) -> f64 {
-        self.get_horizontal_aim_direction() * -1.0
+        -self.get_horizontal_aim_direction()
     }
 
     pub fn get_horizontal_aim_direction(&self) -> f64 {
@@ -97,16 +134,43 @@ impl Player {
                 let mut dir = Vector2::new(horizontal, -1.0);
                 dir.normalize();
                 dir
+            },
+            ThrowDirection::Custom(dir) => {
+                dir.normalized()
+            },
+            ThrowDirection::Curved { base, .. } => {
+                base.normalized()
             }
         }
     }
 
+    pub fn speed_multiplier(&self, carrying: bool) -> f64 {
+        if carrying {
+            self.config.carry_speed_multiplier
+        } else {
+            1.0
+        }
+    }
+
+    pub fn can_dash(&self) -> bool {
+        self.slide.is_none() && self.dash_cooldown == 0
+    }
+
+    // Respawn slides aren't dashes, they count as 0
+    pub fn dash_frames_remaining(&self) -> u32 {
+        match self.slide {
+            Some(Slide { frames_left: Some(frames), .. }) => frames,
+            _ => 0,
+        }
+    }
+
     pub fn dash(&mut self, dir: Vector2) {
-        if self.slide.is_none() {
+        if self.can_dash() {
             self.slide = Some(Slide {
                 origin: self.pos,
                 target: self.pos + dir,
                 dir: dir.normalized(),
+                frames_left: Some(self.config.dash_duration_frames),
             });
         }
     }
@@ -115,7 +179,33 @@ impl Player {
         self.slide = Some(Slide {
             origin: self.pos,
             target: pos,
-            dir: (pos - self.pos).normalized()
+            dir: (pos - self.pos).normalized(),
+            frames_left: None,
         });
     }
+
+    // Moves the player along its slide for one frame, slower when `carrying` the frisbee
+    pub fn update_slide(&mut self, carrying: bool) {
+        if let Some(mut slide) = self.slide {
+            self.pos += slide.dir * self.config.dash_speed * self.speed_multiplier(carrying) * 0.1;
+            if slide.has_reached_goal(&self.pos) {
+                self.pos = slide.target;
+                self.end_slide();
+                return;
+            }
+            match slide.frames_left {
+                Some(frames) if frames <= 1 => self.end_slide(),
+                Some(frames) => {
+                    slide.frames_left = Some(frames - 1);
+                    self.slide = Some(slide);
+                },
+                None => {}
+            };
+        }
+    }
+
+    pub fn end_slide(&mut self) {
+        self.slide = None;
+        self.dash_cooldown = self.config.dash_cooldown_frames;
+    }
 }
@@ -0,0 +1,230 @@
+use game_engine::GameEngine;
+use agent::{ Intent, HumanIntent, AgentType };
+use frisbee::ThrowDirection;
+use player::PlayerSide;
+use vector2::Vector2;
+
+use std::io::{ Read, Write, Error, ErrorKind };
+
+// A match is recorded as the intents applied on each frame rather than the agents' inputs,
+// so replaying it doesn't depend on the agents (or their randomness) at all
+pub struct Recorder {
+    pub recording: bool,
+    pub serve:     Option<PlayerSide>,
+    pub frames:    Vec<(Intent, Intent)>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self {
+            recording: false,
+            serve:     None,
+            frames:    Vec::new(),
+        }
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = true;
+        self.serve = None;
+        self.frames.clear();
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recording = false;
+    }
+
+    pub fn push(&mut self, left: Intent, right: Intent) {
+        if self.recording {
+            self.frames.push((left, right));
+        }
+    }
+
+    // Same as `GameEngine::epoch`, recording the intents chosen by the agents
+    pub fn epoch(&mut self, engine: &mut GameEngine, p1_h_action: HumanIntent, p2_h_action: HumanIntent) {
+        let intents = engine.select_intents(p1_h_action, p2_h_action);
+        self.push(intents.0, intents.1);
+        engine.step(intents);
+
+        // The first serve is the only random event of the engine itself, the following ones target the last round's loser
+        if self.recording && self.serve.is_none() {
+            self.serve = engine.frisbee.last_held;
+        }
+    }
+
+    // Layout: serve (i8), number of frames (u64), then the left and right intents of each frame
+    pub fn save(&self, path: &str) -> ::std::io::Result<()> {
+        use ::std::fs::File;
+        use ::std::io::BufWriter;
+
+        let mut bw = BufWriter::new(File::create(path)?);
+        bw.write_all(&[::player::player_side_to_i8(self.serve) as u8])?;
+        bw.write_all(&(self.frames.len() as u64).to_le_bytes())?;
+        for (left, right) in self.frames.iter() {
+            write_intent(&mut bw, left)?;
+            write_intent(&mut bw, right)?;
+        }
+        bw.flush()
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Replayer {
+    pub serve:  Option<PlayerSide>,
+    pub frames: Vec<(Intent, Intent)>,
+    pub index:  usize,
+}
+
+impl Replayer {
+    pub fn load(path: &str) -> ::std::io::Result<Self> {
+        use ::std::fs::File;
+        use ::std::io::BufReader;
+
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut br = BufReader::new(file);
+        let serve = ::player::player_side_from_i8(read_u8(&mut br)? as i8);
+        let len = read_u64(&mut br)?;
+        // Every intent takes at least a byte, a count the file can't hold is corrupt and mustn't be allocated
+        if len > file_len.saturating_sub(9) / 2 {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Invalid number of frames {}.", len)));
+        }
+        let mut frames = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let left = read_intent(&mut br)?;
+            let right = read_intent(&mut br)?;
+            frames.push((left, right));
+        }
+
+        Ok(Self {
+            serve,
+            frames,
+            index: 0,
+        })
+    }
+
+    // A freshly reset engine in the same starting conditions as the recorded match
+    pub fn new_engine(&self, seed: u64) -> GameEngine {
+        let mut engine = GameEngine::new();
+        engine.seed_rng(seed);
+        // Agents are never asked to act during a replay but stepping requires them
+        engine.send_type_p1(AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.send_type_p2(AgentType::HumanPlayer as i8, 0.0, 0);
+        engine.reset();
+        engine.frisbee.last_held = self.serve;
+        engine
+    }
+
+    // Steps `engine` through the remaining recorded frames, returns the number of frames played
+    pub fn play(&mut self, engine: &mut GameEngine) -> usize {
+        let mut count = 0;
+        for intents in self.by_ref() {
+            engine.step(intents);
+            count += 1;
+            if engine.is_over() {
+                break;
+            }
+        }
+        count
+    }
+}
+
+impl Iterator for Replayer {
+    type Item = (Intent, Intent);
+
+    fn next(&mut self) -> Option<(Intent, Intent)> {
+        let frame = self.frames.get(self.index).cloned();
+        if frame.is_some() {
+            self.index += 1;
+        }
+        frame
+    }
+}
+
+fn write_intent(w: &mut dyn Write, intent: &Intent) -> ::std::io::Result<()> {
+    match intent {
+        Intent::None => w.write_all(&[0]),
+        Intent::Move(dir) => {
+            w.write_all(&[1])?;
+            write_vector(w, dir)
+        },
+        Intent::Dash(dir) => {
+            w.write_all(&[2])?;
+            write_vector(w, dir)
+        },
+        Intent::Throw(dir) => {
+            w.write_all(&[3])?;
+            match dir {
+                ThrowDirection::Up => w.write_all(&[0]),
+                ThrowDirection::LightUp => w.write_all(&[1]),
+                ThrowDirection::Middle => w.write_all(&[2]),
+                ThrowDirection::LightDown => w.write_all(&[3]),
+                ThrowDirection::Down => w.write_all(&[4]),
+                ThrowDirection::Custom(dir) => {
+                    w.write_all(&[5])?;
+                    write_vector(w, dir)
+                },
+                ThrowDirection::Curved { base, curve } => {
+                    w.write_all(&[6])?;
+                    write_vector(w, base)?;
+                    w.write_all(&curve.to_le_bytes())
+                },
+            }
+        },
+        Intent::Grab => w.write_all(&[4]),
+    }
+}
+
+fn write_vector(w: &mut dyn Write, v: &Vector2) -> ::std::io::Result<()> {
+    w.write_all(&v.x.to_le_bytes())?;
+    w.write_all(&v.y.to_le_bytes())
+}
+
+fn read_intent(r: &mut dyn Read) -> ::std::io::Result<Intent> {
+    let intent = match read_u8(r)? {
+        0 => Intent::None,
+        1 => Intent::Move(read_vector(r)?),
+        2 => Intent::Dash(read_vector(r)?),
+        3 => Intent::Throw(match read_u8(r)? {
+            0 => ThrowDirection::Up,
+            1 => ThrowDirection::LightUp,
+            2 => ThrowDirection::Middle,
+            3 => ThrowDirection::LightDown,
+            4 => ThrowDirection::Down,
+            5 => ThrowDirection::Custom(read_vector(r)?),
+            6 => ThrowDirection::Curved { base: read_vector(r)?, curve: read_f64(r)? },
+            d => return Err(Error::new(ErrorKind::InvalidData, format!("Invalid throw direction {}.", d))),
+        }),
+        4 => Intent::Grab,
+        t => return Err(Error::new(ErrorKind::InvalidData, format!("Invalid intent tag {}.", t))),
+    };
+    Ok(intent)
+}
+
+fn read_vector(r: &mut dyn Read) -> ::std::io::Result<Vector2> {
+    let x = read_f64(r)?;
+    let y = read_f64(r)?;
+    Ok(Vector2::new(x, y))
+}
+
+fn read_u8(r: &mut dyn Read) -> ::std::io::Result<u8> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u64(r: &mut dyn Read) -> ::std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_f64(r: &mut dyn Read) -> ::std::io::Result<f64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(f64::from_le_bytes(buf))
+}
@@ -2,6 +2,7 @@ use player::PlayerSide;
 use game_engine::GameEngine;
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct SharedData {
     pub p1_x:          f64,
     pub p1_y:          f64,
@@ -60,6 +61,6 @@ impl SharedData {
         engine.frisbee.held_by_player = ::player::player_side_from_i8(self.zbee_held);
 
         engine.time = self.time;
-        engine.state_of_game = ::game_engine::state_from_i8(self.state_of_game);
+        engine.state_of_game = ::game_engine::state_from_i8(self.state_of_game, (self.p1_score, self.p2_score));
     }
 }
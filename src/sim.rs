@@ -0,0 +1,176 @@
+use game_engine::{ GameEngine, StateOfGame, StepResult };
+use agent::{ Agent, AgentType, AgentParams, HumanIntent };
+use shared_data::SharedData;
+use player::PlayerSide;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct MatchStats {
+    pub left_wins:      u32,
+    pub right_wins:     u32,
+    pub draws:          u32,
+    pub average_frames: f64,
+}
+
+// Plays `n` matches without rendering, each one until it ends or `max_frames` have been played
+// Match `i` is seeded with `seed + i`, so a batch is reproducible as long as the agents only draw from the engine rngs
+pub fn run_matches(left: AgentType, right: AgentType, n: u32, max_frames: u32, seed: u64) -> MatchStats {
+    let params = AgentParams::default();
+    let mut engine = GameEngine::new();
+    engine.send_type_p1(left as i8, params.frames, params.sim);
+    engine.send_type_p2(right as i8, params.frames, params.sim);
+
+    let mut stats = MatchStats::default();
+    let mut total_frames: u64 = 0;
+    for i in 0..n {
+        engine.reset();
+        engine.seed_rng(seed.wrapping_add(i as u64));
+
+        let mut frames = 0;
+        while frames < max_frames && !engine.is_over() {
+            // Human players stand still
+            engine.epoch(HumanIntent::IDLE, HumanIntent::IDLE);
+            frames += 1;
+        }
+        total_frames += frames as u64;
+
+        // Matches cut by `max_frames` are decided on the scores
+        let winner = match engine.state_of_game {
+            StateOfGame::Won(side) => Some(side),
+            StateOfGame::Draw => None,
+            _ => ::game_engine::winner((engine.players.0.score, engine.players.1.score)),
+        };
+        match winner {
+            Some(PlayerSide::Left) => stats.left_wins += 1,
+            Some(PlayerSide::Right) => stats.right_wins += 1,
+            None => stats.draws += 1,
+        }
+    }
+
+    if n > 0 {
+        stats.average_frames = total_frames as f64 / n as f64;
+    }
+    stats
+}
+
+// A whole match as a lazy sequence of frames, yielding the state after each one until the match ends or is a draw
+pub struct MatchIter {
+    pub engine: GameEngine,
+}
+
+impl MatchIter {
+    pub fn new(mut engine: GameEngine, left: Box<dyn Agent>, right: Box<dyn Agent>) -> Self {
+        engine.agents = (Some(left), Some(right));
+        Self {
+            engine,
+        }
+    }
+
+    pub fn into_engine(self) -> GameEngine {
+        self.engine
+    }
+}
+
+impl Iterator for MatchIter {
+    type Item = SharedData;
+
+    fn next(&mut self) -> Option<SharedData> {
+        if self.engine.is_over() {
+            return None;
+        }
+
+        self.engine.epoch(HumanIntent::IDLE, HumanIntent::IDLE);
+        let mut state = SharedData::new();
+        self.engine.to_shared_data(&mut state);
+        Some(state)
+    }
+}
+
+// Independent engines stepped in lockstep by an external learner, one per seed
+// Engines whose match is over are reset on the step that ended it, so every step of `step_all` can be trained on
+pub struct VecEnv {
+    pub envs: Vec<GameEngine>,
+}
+
+impl VecEnv {
+    pub fn new(seeds: &[u64]) -> Self {
+        let envs = seeds.iter()
+            .map(|&seed| {
+                let mut engine = GameEngine::new();
+                // Actions drive the players, the agents are only there because stepping requires them
+                engine.send_type_p1(AgentType::HumanPlayer as i8, 0.0, 0);
+                engine.send_type_p2(AgentType::HumanPlayer as i8, 0.0, 0);
+                engine.seed_rng(seed);
+                engine.reset();
+                engine
+            })
+            .collect();
+        Self {
+            envs,
+        }
+    }
+
+    pub fn reset_all(&mut self) {
+        for env in self.envs.iter_mut() {
+            env.reset();
+        }
+    }
+
+    // Steps engine `i` with the left and right action indices `actions[i]`, see `GameEngine::step_rl`
+    pub fn step_all(&mut self, actions: &[(u8, u8)]) -> Vec<StepResult> {
+        assert_eq!(actions.len(), self.envs.len(), "One pair of actions per environment.");
+        self.envs.iter_mut().zip(actions.iter())
+            .map(|(env, &(left, right))| {
+                let result = env.step_rl(left, right);
+                if env.is_over() {
+                    env.reset();
+                }
+                result
+            })
+            .collect()
+    }
+}
+
+// What a training episode ended on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EpisodeMetrics {
+    pub episode:          u32,
+    pub left_score:       i8,
+    pub right_score:      i8,
+    pub epsilon:          f32,
+    pub nonzero_q_states: usize,
+}
+
+// Per-episode training curves, for plotting scores and exploration over a run
+#[derive(Clone, Debug, Default)]
+pub struct MetricsLogger {
+    pub episodes: Vec<EpisodeMetrics>,
+}
+
+impl MetricsLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Records the episode `engine` is ending, episodes are numbered from 0 in the order they are logged
+    pub fn log_episode(&mut self, engine: &GameEngine) {
+        self.episodes.push(EpisodeMetrics {
+            episode:          self.episodes.len() as u32,
+            left_score:       engine.players.0.score,
+            right_score:      engine.players.1.score,
+            epsilon:          engine.explo_rate,
+            nonzero_q_states: engine.q_table_nonzero_count(),
+        });
+    }
+
+    pub fn to_csv(&self, path: &str) -> ::std::io::Result<()> {
+        use ::std::fs::File;
+        use ::std::io::{ BufWriter, Write };
+
+        let mut bw = BufWriter::new(File::create(path)?);
+        writeln!(bw, "episode,left_score,right_score,epsilon,nonzero_q_states")?;
+        for m in self.episodes.iter() {
+            writeln!(bw, "{},{},{},{},{}", m.episode, m.left_score, m.right_score, m.epsilon, m.nonzero_q_states)?;
+        }
+        bw.flush()
+    }
+}
@@ -0,0 +1,94 @@
+use game_engine::GameEngine;
+use shared_data::SharedData;
+use agent::{ Intent, HumanIntent };
+
+pub struct FrameRecord {
+    pub frame:   u64,
+    pub intents: (Intent, Intent),
+    pub state:   SharedData,
+}
+
+// Wraps an engine so a frontend can pause it and advance it one frame at a time
+// while keeping a trace of what the agents chose on each frame
+pub struct StepController {
+    pub engine:      GameEngine,
+    pub paused:      bool,
+    pub frame:       u64,
+    pub history:     Vec<FrameRecord>,
+    pub log_to_file: bool,
+}
+
+impl StepController {
+    pub fn new(engine: GameEngine) -> Self {
+        Self {
+            engine,
+            paused:      false,
+            frame:       0,
+            history:     Vec::new(),
+            log_to_file: false,
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Called once per frontend frame, only advances the engine when it is not paused
+    pub fn tick(&mut self, p1_h_action: HumanIntent, p2_h_action: HumanIntent) -> Option<&FrameRecord> {
+        if self.paused {
+            return None;
+        }
+        Some(self.advance(p1_h_action, p2_h_action))
+    }
+
+    // Advances exactly one frame, whether the controller is paused or not
+    pub fn advance(&mut self, p1_h_action: HumanIntent, p2_h_action: HumanIntent) -> &FrameRecord {
+        // Same sequence as `GameEngine::epoch`, so stepping manually doesn't change the simulation
+        let intents = self.engine.select_intents(p1_h_action, p2_h_action);
+        self.engine.step(intents);
+
+        let mut state = SharedData::new();
+        self.engine.to_shared_data(&mut state);
+
+        self.frame += 1;
+        let record = FrameRecord {
+            frame: self.frame,
+            intents,
+            state,
+        };
+        if self.log_to_file {
+            self.engine.log(&format_record(&record));
+        }
+        self.history.push(record);
+
+        self.history.last().unwrap()
+    }
+
+    pub fn last_record(&self) -> Option<&FrameRecord> {
+        self.history.last()
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+}
+
+pub fn format_record(record: &FrameRecord) -> String {
+    format!(
+        "frame {}: p1 {:?} -> ({:.3}, {:.3}) | p2 {:?} -> ({:.3}, {:.3}) | frisbee ({:.3}, {:.3}) held {} | score {} - {} | state {}",
+        record.frame,
+        record.intents.0, record.state.p1_x, record.state.p1_y,
+        record.intents.1, record.state.p2_x, record.state.p2_y,
+        record.state.zbee_x, record.state.zbee_y, record.state.zbee_held,
+        record.state.p1_score, record.state.p2_score,
+        record.state.state_of_game,
+    )
+}
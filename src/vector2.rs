@@ -1,6 +1,9 @@
 use std::ops::{ Mul, MulAssign, Div, DivAssign, Add, AddAssign, Sub, SubAssign };
 
+pub const NORMALIZE_EPSILON: f64 = 1e-12;
+
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Vector2 {
     pub x: f64,
     pub y: f64
@@ -24,20 +27,67 @@ impl Vector2 {
         (self.x.powi(2) + self.y.powi(2)).sqrt()
     }
 
+    // Vectors shorter than `NORMALIZE_EPSILON` have no direction, they become the zero vector rather than NaN or a huge one
     pub fn normalize(&mut self) {
         let len = self.length();
-        if len != 0.0 {
-            assert_ne!(len, 0.0f64);
-            self.x = self.x / len;
-            self.y = self.y / len;
+        if len < NORMALIZE_EPSILON {
+            *self = Vector2::zero();
+        } else {
+            self.x /= len;
+            self.y /= len;
         }
     }
 
     pub fn normalized(&self) -> Self {
-        let mut cpy = self.clone();
+        let mut cpy = *self;
         cpy.normalize();
         cpy
     }
+
+    pub fn distance(&self, other: &Vector2) -> f64 {
+        (*self - *other).length()
+    }
+
+    pub fn dot(&self, other: &Vector2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // Z component of the 3D cross product, positive when `other` is counter-clockwise from `self`
+    pub fn cross(&self, other: &Vector2) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    // Counter-clockwise rotation
+    pub fn rotate(&self, radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+
+    // True if both coordinates are within `eps` of the other's, positions seldom compare exactly after a few steps
+    pub fn approx_eq(&self, other: &Vector2, eps: f64) -> bool {
+        (self.x - other.x).abs() <= eps && (self.y - other.y).abs() <= eps
+    }
+
+    // Angle from the x axis in [-PI, PI], 0 for the zero vector
+    pub fn angle(&self) -> f64 {
+        if self.x == 0.0 && self.y == 0.0 {
+            return 0.0;
+        }
+        self.y.atan2(self.x)
+    }
+
+    // Unsigned angle in [0, PI], 0 if either vector is zero
+    pub fn angle_between(&self, other: &Vector2) -> f64 {
+        let len = self.length() * other.length();
+        if len == 0.0 {
+            return 0.0;
+        }
+        let cos = self.dot(other) / len;
+        cos.clamp(-1.0, 1.0).acos()
+    }
 }
 
 impl Mul for Vector2 {